@@ -0,0 +1,72 @@
+#!/usr/bin/env python3
+# Prints the vectors of test_mac_algorithms in src/mac/cbcmac.rs: the MAC
+# algorithms 1, 2, 3, 6 and 4 of GB/T 15852.1 (ISO/IEC 9797-1), over the SM4
+# block cipher of the Python cryptography package (pip install cryptography).
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+
+
+def enc(key, block):
+    e = Cipher(algorithms.SM4(key), modes.ECB()).encryptor()
+    return e.update(block) + e.finalize()
+
+
+def dec(key, block):
+    d = Cipher(algorithms.SM4(key), modes.ECB()).decryptor()
+    return d.update(block) + d.finalize()
+
+
+def xor(a, b):
+    return bytes(x ^ y for x, y in zip(a, b))
+
+
+def pad(data, method):
+    if method == 1:
+        out = data if data else bytes(16)
+    elif method == 2:
+        out = data + b"\x80"
+    else:
+        out = (len(data) * 8).to_bytes(16, "big") + data
+    return out + bytes(-len(out) % 16)
+
+
+def cbc(key, h, blocks):
+    for i in range(0, len(blocks), 16):
+        h = enc(key, xor(h, blocks[i:i + 16]))
+    return h
+
+
+def mac1(k, data, method):
+    return cbc(k, bytes(16), pad(data, method))
+
+
+def mac2(k, k1, data, method):
+    return enc(k1, mac1(k, data, method))
+
+
+def mac3(k, k1, data, method):
+    return enc(k, dec(k1, mac1(k, data, method)))
+
+
+def mac4(k, k1, k2, data, method):
+    d = pad(data, method)
+    if len(d) < 32:
+        return None
+    return enc(k1, cbc(k, enc(k2, enc(k, d[:16])), d[16:]))
+
+
+def mac6(k, k1, data, method):
+    d = pad(data, method)
+    return enc(k1, xor(cbc(k, bytes(16), d[:-16]), d[-16:]))
+
+
+k = bytes.fromhex("0123456789ABCDEFFEDCBA9876543210")
+k1 = bytes.fromhex("FEDCBA98765432100123456789ABCDEF")
+k2 = bytes.fromhex("00112233445566778899AABBCCDDEEFF")
+msg = b"AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDD"
+for n, method in [(0, 1), (0, 2), (16, 1), (16, 2), (20, 1), (20, 3)]:
+    data = msg[:n]
+    print(n, "Method%d" % method)
+    for m in [mac1(k, data, method), mac2(k, k1, data, method), mac3(k, k1, data, method), mac6(k, k1, data, method)]:
+        print("   ", m.hex())
+    m4 = mac4(k, k1, k2, data, method)
+    print("    4:", m4.hex() if m4 else None)
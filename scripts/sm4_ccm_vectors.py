@@ -0,0 +1,49 @@
+#!/usr/bin/env python3
+# Prints the SM4-CCM vectors of test_ccm in src/blockmode/ccm.rs.
+# CCM of NIST SP 800-38C, over the SM4 block cipher of the Python
+# cryptography package (pip install cryptography).
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+
+
+def sm4_ecb(key, block):
+    e = Cipher(algorithms.SM4(key), modes.ECB()).encryptor()
+    return e.update(block) + e.finalize()
+
+
+def xor(a, b):
+    return bytes(x ^ y for x, y in zip(a, b))
+
+
+def pad16(b):
+    return b + bytes(-len(b) % 16)
+
+
+def ccm_seal(key, nonce, plain, add, tag_size):
+    q = 15 - len(nonce)
+    flags = (64 if add else 0) | (tag_size - 2) // 2 << 3 | (q - 1)
+    b = bytes([flags]) + nonce + len(plain).to_bytes(q, "big")
+    if add:
+        # the additional data shorter than 2^16 - 2^8 has a 2-byte length.
+        b += pad16(len(add).to_bytes(2, "big") + add)
+    b += pad16(plain)
+    mac = bytes(16)
+    for i in range(0, len(b), 16):
+        mac = sm4_ecb(key, xor(mac, b[i:i + 16]))
+
+    def ctr(i):
+        return sm4_ecb(key, bytes([q - 1]) + nonce + i.to_bytes(q, "big"))
+
+    stream = b"".join(ctr(i) for i in range(1, len(plain) // 16 + 2))
+    return xor(plain, stream) + xor(mac, ctr(0))[:tag_size]
+
+
+key = bytes.fromhex("0123456789ABCDEFFEDCBA9876543210")
+plain = bytes.fromhex(
+    "AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDD"
+    "EEEEEEEEEEEEEEEEFFFFFFFFFFFFFFFFEEEEEEEEEEEEEEEEAAAAAAAAAAAAAAAA"
+)
+add = bytes.fromhex("FEEDFACEDEADBEEFFEEDFACEDEADBEEFABADDAD2")
+nonce = bytes.fromhex("00001234567800000000ABCD")
+print("RFC 8998 A.2", ccm_seal(key, nonce, plain, add, 16).hex())
+print("<7, 4>      ", ccm_seal(key, nonce[:7], plain[:20], None, 4).hex())
+print("<13, 10>    ", ccm_seal(key, bytes(range(13)), plain[:17], add, 10).hex())
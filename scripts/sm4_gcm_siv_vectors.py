@@ -0,0 +1,79 @@
+#!/usr/bin/env python3
+# Prints the SM4-GCM-SIV vectors of test_gcm_siv in src/blockmode/gcm_siv.rs.
+# GCM-SIV of RFC 8452 with SM4 in place of AES-128, the block ciphers are of
+# the Python cryptography package (pip install cryptography). The same code
+# over AES is checked against AESGCMSIV of the package first.
+import os
+
+from cryptography.hazmat.primitives.ciphers import Cipher, algorithms, modes
+from cryptography.hazmat.primitives.ciphers.aead import AESGCMSIV
+
+# x^128 + x^127 + x^126 + x^121 + 1, the elements are little-endian.
+POLY = (1 << 128) | (1 << 127) | (1 << 126) | (1 << 121) | 1
+
+
+def gf_mul(a, b):
+    p = 0
+    while b:
+        if b & 1:
+            p ^= a
+        b >>= 1
+        a <<= 1
+        if a >> 128:
+            a ^= POLY
+    return p
+
+
+# x^-128, with x^-1 = x^127 + x^126 + x^125 + x^120.
+X_INV_128 = 1
+for _ in range(128):
+    X_INV_128 = gf_mul(X_INV_128, (1 << 127) | (1 << 126) | (1 << 125) | (1 << 120))
+
+
+def polyval(h, data):
+    h = int.from_bytes(h, "little")
+    s = 0
+    for i in range(0, len(data), 16):
+        s = gf_mul(gf_mul(s ^ int.from_bytes(data[i:i + 16], "little"), h), X_INV_128)
+    return s.to_bytes(16, "little")
+
+
+def pad16(b):
+    return b + bytes(-len(b) % 16)
+
+
+def xor(a, b):
+    return bytes(x ^ y for x, y in zip(a, b))
+
+
+def gcm_siv_seal(algorithm, key, nonce, plain, add):
+    def enc(k, block):
+        e = Cipher(algorithm(k), modes.ECB()).encryptor()
+        return e.update(block) + e.finalize()
+
+    derived = [enc(key, i.to_bytes(4, "little") + nonce)[:8] for i in range(4)]
+    auth_key, enc_key = b"".join(derived[:2]), b"".join(derived[2:])
+    lengths = (len(add) * 8).to_bytes(8, "little") + (len(plain) * 8).to_bytes(8, "little")
+    s = bytearray(xor(polyval(auth_key, pad16(add) + pad16(plain) + lengths), nonce + bytes(4)))
+    s[15] &= 0x7F
+    tag = enc(enc_key, bytes(s))
+    counter = bytearray(tag)
+    counter[15] |= 0x80
+    ctr = int.from_bytes(counter[:4], "little")
+    stream = b""
+    for i in range((len(plain) + 15) // 16):
+        c = ((ctr + i) & 0xFFFFFFFF).to_bytes(4, "little") + bytes(counter[4:])
+        stream += enc(enc_key, c)
+    return xor(plain, stream) + tag
+
+
+for n, a in [(0, 0), (0, 20), (9, 0), (32, 20), (70, 20), (100, 33)]:
+    key, nonce, plain, add = os.urandom(16), os.urandom(12), os.urandom(n), os.urandom(a)
+    assert gcm_siv_seal(algorithms.AES, key, nonce, plain, add) == AESGCMSIV(key).encrypt(nonce, plain, add)
+
+key = bytes.fromhex("0123456789ABCDEFFEDCBA9876543210")
+nonce = bytes.fromhex("00001234567800000000ABCD")
+msg = b"AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDDEEEEEE"
+add = bytes.fromhex("FEEDFACEDEADBEEFFEEDFACEDEADBEEFABADDAD2")
+for n, a in [(0, 0), (0, 20), (9, 0), (32, 20), (70, 20)]:
+    print(n, a, gcm_siv_seal(algorithms.SM4, key, nonce, msg[:n], add[:a]).hex())
@@ -1,4 +1,7 @@
+use alloc::vec::Vec;
+
 use crate::traits::Block;
+use super::padding::Padding;
 use super::{Error, Result};
 
 pub struct CBCMode<B:Block>{
@@ -69,6 +72,30 @@ impl<B:Block> CBCMode<B>{
         Ok(())
     }
 
+    // Pads data and encrypts it into a new vector.
+    pub fn encrypt_padded_vec(&self, iv: &[u8], data: &[u8], padding: Padding) -> Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(data.len() + self.block_size);
+        out.extend_from_slice(data);
+        padding.pad(&mut out, self.block_size);
+        self.encrypt_inplace(iv, &mut out)?;
+        Ok(out)
+    }
+
+    // Decrypts data and removes the padding, the plaintext is zeroed on a bad padding.
+    pub fn decrypt_padded_vec(&self, iv: &[u8], data: &[u8], padding: Padding) -> Result<Vec<u8>> {
+        let mut out = data.to_vec();
+        self.decrypt_inplace(iv, &mut out)?;
+        match padding.unpad(&out, self.block_size) {
+            Ok(n) => {
+                out.truncate(n);
+                Ok(out)
+            }
+            Err(e) => {
+                out.fill(0);
+                Err(e)
+            }
+        }
+    }
 }
 
 
@@ -76,6 +103,8 @@ impl<B:Block> CBCMode<B>{
 mod tests{
     use hex_literal::hex;
 
+    use crate::blockmode::padding::Padding;
+    use crate::blockmode::Error;
     use crate::sm4::Cipher;
 
     use super::CBCMode;
@@ -94,4 +123,32 @@ mod tests{
         cbc.decrypt_inplace(&iv, &mut plain).unwrap();
         assert_eq!(plain, hex!("7B5BD9FDAE2521A3F0FBDD2F4427142F785C52080B0DB22523C3BC5D8716D141CE315586EBB3EDF4480193B1B3C33524"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_cbc_padded() {
+        // openssl enc -sm4-cbc
+        let key = hex!("0123456789ABCDEFFEDCBA9876543210");
+        let iv = hex!("000102030405060708090A0B0C0D0E0F");
+        let tests: [(usize, &[u8]); 3] = [
+            (0, &hex!("4b910651754b5553f10cfa0c8a09e9e5")),
+            (20, &hex!("be3f4703934470c710623f9140b1444c634d0c8928df2a9a549cb4de9ef7235e")),
+            (32, &hex!("be3f4703934470c710623f9140b1444cbf6101d525df01113437e6f7875224fe739cacd58bcf601a591e985f68e060f7")),
+        ];
+        let cbc = CBCMode::new(Cipher::new(&key));
+        for (n, wanted) in tests {
+            let plain = [b'a'; 32];
+            let ciphertext = cbc.encrypt_padded_vec(&iv, &plain[..n], Padding::PKCS7).unwrap();
+            assert_eq!(ciphertext, wanted);
+            assert_eq!(cbc.decrypt_padded_vec(&iv, &ciphertext, Padding::PKCS7).unwrap(), plain[..n]);
+        }
+
+        for padding in [Padding::ISO7816, Padding::ANSIX923, Padding::Zero] {
+            let ciphertext = cbc.encrypt_padded_vec(&iv, b"abcdefg", padding).unwrap();
+            assert_eq!(ciphertext.len(), 16);
+            assert_eq!(cbc.decrypt_padded_vec(&iv, &ciphertext, padding).unwrap(), b"abcdefg");
+        }
+        let mut ciphertext = cbc.encrypt_padded_vec(&iv, b"abcdefg", Padding::PKCS7).unwrap();
+        ciphertext[15] ^= 1;
+        assert!(matches!(cbc.decrypt_padded_vec(&iv, &ciphertext, Padding::PKCS7), Err(Error::InvalidPadding)));
+    }
+}
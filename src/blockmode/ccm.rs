@@ -0,0 +1,314 @@
+use subtle::ConstantTimeEq;
+
+use super::ctr::CTRMode;
+use super::{Error, Result};
+use crate::sm4;
+use crate::traits::Block;
+use crate::traits::AEAD;
+
+const BLOCK_SIZE: usize = 16;
+const TAG_SIZE: usize = 16;
+const STD_NONCE_SIZE: usize = 12;
+const MIN_NONCE_SIZE: usize = 7;
+const MAX_NONCE_SIZE: usize = 13;
+
+// Returns a CCM instance with nonce size 12 and tag size 16, as RFC 8998.
+// The input must have length 16.
+pub fn new_sm4_ccm_std(key: &[u8]) -> Sm4Ccm<STD_NONCE_SIZE, TAG_SIZE> {
+    Sm4Ccm::<STD_NONCE_SIZE, TAG_SIZE>::new(sm4::Cipher::new(&key))
+}
+
+pub type Sm4Ccm<const N: usize, const T: usize> = CCM<sm4::Cipher, N, T>;
+
+// CCM is the Counter with CBC-MAC of NIST SP 800-38C and RFC 3610.
+// The nonce size N is 7..=13, the length of the message is encoded in the
+// 15-N bytes left, and the tag size T is one of 4, 6, ..., 16.
+pub struct CCM<B: Block, const N: usize, const T: usize> {
+    pub cipher: B,
+}
+
+impl<B: Block, const N: usize, const T: usize> CCM<B, N, T> {
+    // Panics if N or T is invalid, see try_new.
+    pub fn new(block: B) -> Self {
+        Self::try_new(block).unwrap()
+    }
+
+    // The nonce size N is 7..=13, and the tag size T is one of 4, 6, ..., 16.
+    pub fn try_new(block: B) -> Result<Self> {
+        // reports the nearest valid size.
+        if !(MIN_NONCE_SIZE..=MAX_NONCE_SIZE).contains(&N) {
+            return Err(Error::InvalidNonceSize(N.clamp(MIN_NONCE_SIZE, MAX_NONCE_SIZE), N));
+        }
+        if !(4..=TAG_SIZE).contains(&T) || T % 2 != 0 {
+            return Err(Error::InvalidTagSize(T));
+        }
+        debug_assert_eq!(block.block_size(), BLOCK_SIZE);
+        Ok(CCM { cipher: block })
+    }
+}
+
+impl<B: Block, const N: usize, const T: usize> AEAD for CCM<B, N, T> {
+    type Error = super::Error;
+
+    fn overhead(&self) -> usize {
+        T
+    }
+
+    fn nonce_size(&self) -> usize {
+        N
+    }
+
+    fn seal_inplace(
+        &self,
+        in_out: &mut [u8],
+        tag: &mut [u8],
+        nonce: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<()> {
+        self.check(nonce, in_out.len())?;
+        if tag.len() < T {
+            return Err(Error::OutputTooSmall(T, tag.len()));
+        }
+        self.auth(&mut tag[..T], in_out, add, nonce);
+        self.ctr(nonce).xor_key_stream_inplace(in_out);
+        Ok(())
+    }
+
+    fn open_inplace(
+        &self,
+        in_out: &mut [u8],
+        tag: &[u8],
+        nonce: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<()> {
+        if nonce.len() != N || tag.len() != T {
+            return Err(Error::CCMAuthenticationError);
+        }
+        self.check(nonce, in_out.len())?;
+        self.ctr(nonce).xor_key_stream_inplace(in_out);
+
+        let mut expected_tag = [0; T];
+        self.auth(&mut expected_tag, in_out, add, nonce);
+        if expected_tag.ct_eq(tag).unwrap_u8() != 1 {
+            in_out.fill(0);
+            return Err(Error::CCMAuthenticationError);
+        }
+        Ok(())
+    }
+
+    fn seal(
+        &self,
+        out: &mut [u8],
+        nonce: &[u8],
+        plaintext: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<()> {
+        let plaintext_length = plaintext.len();
+        if out.len() < plaintext_length + T {
+            return Err(Error::OutputTooSmall(plaintext_length + T, out.len()));
+        }
+
+        let (ciphertext, tag) = out.split_at_mut(plaintext_length);
+        ciphertext.copy_from_slice(plaintext);
+        self.seal_inplace(ciphertext, &mut tag[..T], nonce, add)
+    }
+
+    fn open(
+        &self,
+        out: &mut [u8],
+        nonce: &[u8],
+        ciphertext: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<usize> {
+        if ciphertext.len() < T {
+            return Err(Error::CCMCiphertextTooSmall(ciphertext.len(), T));
+        }
+
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - T);
+        if out.len() < ciphertext.len() {
+            return Err(Error::OutputBufferTooShort(
+                ciphertext.len(),
+                out.len(),
+            ));
+        }
+        let out = &mut out[..ciphertext.len()];
+        out.copy_from_slice(ciphertext);
+        self.open_inplace(out, tag, nonce, add)?;
+        Ok(ciphertext.len())
+    }
+}
+
+impl<B: Block, const N: usize, const T: usize> CCM<B, N, T> {
+    // The message length must fit in the 15-N bytes.
+    fn check(&self, nonce: &[u8], length: usize) -> Result<()> {
+        if nonce.len() != N {
+            return Err(Error::InvalidNonceSize(N, nonce.len()));
+        }
+        let l = 15 - N;
+        if l < 8 && (length as u64) >> (8 * l) != 0 {
+            return Err(Error::InvalidInputSize);
+        }
+        Ok(())
+    }
+
+    // A_i = flags || nonce || i, the counter is the 15-N bytes.
+    // The keystream of the message starts at A_1.
+    fn ctr(&self, nonce: &[u8]) -> CTRMode<&B> {
+        let mut a1 = [0u8; BLOCK_SIZE];
+        a1[0] = (14 - N) as u8;
+        a1[1..1 + N].copy_from_slice(nonce);
+        a1[BLOCK_SIZE - 1] = 1;
+        CTRMode::new_with_counter_width(&self.cipher, &a1, 8 * (15 - N) as u32).unwrap()
+    }
+
+    // auth calculates the CBC-MAC of B_0 || encoded add || plaintext, and masks
+    // the result with E(A_0).
+    fn auth(&self, tag: &mut [u8], plaintext: &[u8], add: Option<&[u8]>, nonce: &[u8]) {
+        let add = add.unwrap_or(&[]);
+        let mut b0 = [0u8; BLOCK_SIZE];
+        b0[0] = ((!add.is_empty() as u8) << 6) | (((T - 2) / 2) << 3) as u8 | (14 - N) as u8;
+        b0[1..1 + N].copy_from_slice(nonce);
+        b0[1 + N..].copy_from_slice(&(plaintext.len() as u64).to_be_bytes()[8 - (15 - N)..]);
+
+        let mut mac = CBCMac::new(&self.cipher);
+        mac.update(&b0);
+        if !add.is_empty() {
+            let length = add.len() as u64;
+            if length < 0xff00 {
+                mac.update(&(length as u16).to_be_bytes());
+            } else if length <= u32::MAX as u64 {
+                mac.update(&[0xff, 0xfe]);
+                mac.update(&(length as u32).to_be_bytes());
+            } else {
+                mac.update(&[0xff, 0xff]);
+                mac.update(&length.to_be_bytes());
+            }
+            mac.update(add);
+            mac.pad();
+        }
+        mac.update(plaintext);
+        mac.pad();
+
+        let mut s0 = [0u8; BLOCK_SIZE];
+        s0[0] = (14 - N) as u8;
+        s0[1..1 + N].copy_from_slice(nonce);
+        self.cipher.encrypt_inplace(&mut s0);
+        tag.iter_mut()
+            .zip(&mac.x)
+            .zip(&s0)
+            .for_each(|((z, x), s)| *z = *x ^ *s);
+    }
+}
+
+// CBC-MAC with zero IV, the input is padded with zeros by pad.
+struct CBCMac<'a, B: Block> {
+    block: &'a B,
+    x: [u8; BLOCK_SIZE],
+    pos: usize,
+}
+
+impl<'a, B: Block> CBCMac<'a, B> {
+    fn new(block: &'a B) -> Self {
+        CBCMac { block, x: [0; BLOCK_SIZE], pos: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for b in data {
+            self.x[self.pos] ^= *b;
+            self.pos += 1;
+            if self.pos == BLOCK_SIZE {
+                self.block.encrypt_inplace(&mut self.x);
+                self.pos = 0;
+            }
+        }
+    }
+
+    fn pad(&mut self) {
+        if self.pos != 0 {
+            self.block.encrypt_inplace(&mut self.x);
+            self.pos = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use std::vec::Vec;
+
+    const KEY: [u8; 16] = hex!("0123456789ABCDEFFEDCBA9876543210");
+    const PLAIN: [u8; 64] = hex!("AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDDEEEEEEEEEEEEEEEEFFFFFFFFFFFFFFFFEEEEEEEEEEEEEEEEAAAAAAAAAAAAAAAA");
+    const ADD: [u8; 20] = hex!("FEEDFACEDEADBEEFFEEDFACEDEADBEEFABADDAD2");
+
+    fn check<const N: usize, const T: usize>(nonce: &[u8], plain: &[u8], add: Option<&[u8]>, expected: &[u8]) {
+        let c = CCM::<_, N, T>::new(sm4::Cipher::new(&KEY));
+        let mut out = vec![0; plain.len() + c.overhead()];
+        c.seal(&mut out, nonce, plain, add).unwrap();
+        assert_eq!(out, expected);
+
+        let mut decrypted = vec![0; plain.len()];
+        assert_eq!(c.open(&mut decrypted, nonce, &out, add).unwrap(), plain.len());
+        assert_eq!(decrypted, plain);
+
+        let mut in_out = plain.to_vec();
+        let mut tag = [0; T];
+        c.seal_inplace(&mut in_out, &mut tag, nonce, add).unwrap();
+        assert_eq!(in_out, expected[..plain.len()]);
+        assert_eq!(tag, expected[plain.len()..]);
+        c.open_inplace(&mut in_out, &tag, nonce, add).unwrap();
+        assert_eq!(in_out, plain);
+    }
+
+    #[test]
+    fn test_ccm() {
+        // RFC 8998, A.2
+        let nonce = hex!("00001234567800000000ABCD");
+        let expected = hex!("48AF93501FA62ADBCD414CCE6034D895DDA1BF8F132F042098661572E7483094FD12E518CE062C98ACEE28D95DF4416BED31A2F04476C18BB40C84A74B97DC5B16842D4FA186F56AB33256971FA110F4");
+        check::<12, 16>(&nonce, &PLAIN, Some(&ADD), &expected);
+
+        let c = new_sm4_ccm_std(&KEY);
+        let mut out = [0; 80];
+        c.seal(&mut out, &nonce, &PLAIN, Some(&ADD)).unwrap();
+        assert_eq!(out, expected);
+
+        // printed by scripts/sm4_ccm_vectors.py.
+        check::<7, 4>(&nonce[..7], &PLAIN[..20], None, &hex!("94ce2fb9dd8f150d07c7ad1e9a28412a13f022b9ddeaf623"));
+        let nonce: Vec<u8> = (0..13).collect();
+        check::<13, 10>(&nonce, &PLAIN[..17], Some(&ADD), &hex!("78baae27c73c460ac1144e3b5c66b45a7732bd443caab651ff70fc"));
+    }
+
+    #[test]
+    fn test_ccm_errors() {
+        let nonce = hex!("00001234567800000000ABCD");
+        let c = new_sm4_ccm_std(&KEY);
+        let mut sealed = [0; 80];
+        c.seal(&mut sealed, &nonce, &PLAIN, Some(&ADD)).unwrap();
+
+        let mut out = [0xff; 64];
+        for i in [0, 63, 64, 79] {
+            let mut forged = sealed;
+            forged[i] ^= 1;
+            assert!(matches!(c.open(&mut out, &nonce, &forged, Some(&ADD)), Err(Error::CCMAuthenticationError)));
+            assert_eq!(out, [0; 64]);
+        }
+        assert!(matches!(c.open(&mut out, &nonce, &sealed, None), Err(Error::CCMAuthenticationError)));
+        assert!(matches!(c.open(&mut out, &nonce, &sealed[..15], None), Err(Error::CCMCiphertextTooSmall(15, 16))));
+        assert!(matches!(c.seal(&mut out, &nonce[..8], &PLAIN[..8], None), Err(Error::InvalidNonceSize(12, 8))));
+
+        // the length must fit in 15-13 = 2 bytes.
+        let c = CCM::<_, 13, 16>::new(sm4::Cipher::new(&KEY));
+        let mut big = vec![0; 1 << 16];
+        let mut tag = [0; 16];
+        assert!(matches!(c.seal_inplace(&mut big, &mut tag, &[0; 13], None), Err(Error::InvalidInputSize)));
+        c.seal_inplace(&mut big[1..], &mut tag, &[0; 13], None).unwrap();
+
+        let cipher = || sm4::Cipher::new(&KEY);
+        assert!(matches!(CCM::<_, 6, 16>::try_new(cipher()), Err(Error::InvalidNonceSize(7, 6))));
+        assert!(matches!(CCM::<_, 14, 16>::try_new(cipher()), Err(Error::InvalidNonceSize(13, 14))));
+        assert!(matches!(CCM::<_, 12, 2>::try_new(cipher()), Err(Error::InvalidTagSize(2))));
+        assert!(matches!(CCM::<_, 12, 7>::try_new(cipher()), Err(Error::InvalidTagSize(7))));
+        assert!(matches!(CCM::<_, 12, 18>::try_new(cipher()), Err(Error::InvalidTagSize(18))));
+        assert!(CCM::<_, 7, 4>::try_new(cipher()).is_ok());
+    }
+}
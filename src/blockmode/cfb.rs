@@ -0,0 +1,163 @@
+use super::{Error, Result};
+use crate::traits::Block;
+
+const BLOCK_SIZE: usize = 16;
+
+// The segment size s of the CFB-s mode in GB/T 17964 and NIST SP 800-38A.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CFBSegment {
+    Bit1,
+    Bit8,
+    Bit128,
+}
+
+// CFBMode is a stream cipher, xor_key_stream can be called with any length.
+// The CFB1 consumes the bits of each byte from the most significant one.
+pub struct CFBMode<B: Block> {
+    pub block: B,
+    segment: CFBSegment,
+    decrypt: bool,
+    // the input block, the last 128 bits of IV || ciphertext.
+    register: [u8; BLOCK_SIZE],
+    // the output block of CFB128, out[pos..] is not used yet.
+    out: [u8; BLOCK_SIZE],
+    pos: usize,
+}
+
+impl<B: Block> CFBMode<B> {
+    pub fn new_encrypter(block: B, iv: &[u8], segment: CFBSegment) -> Result<Self> {
+        Self::new(block, iv, segment, false)
+    }
+
+    pub fn new_decrypter(block: B, iv: &[u8], segment: CFBSegment) -> Result<Self> {
+        Self::new(block, iv, segment, true)
+    }
+
+    fn new(block: B, iv: &[u8], segment: CFBSegment, decrypt: bool) -> Result<Self> {
+        debug_assert_eq!(block.block_size(), BLOCK_SIZE);
+        if iv.len() != BLOCK_SIZE {
+            return Err(Error::InvalidNonceSize(BLOCK_SIZE, iv.len()));
+        }
+        Ok(CFBMode {
+            block,
+            segment,
+            decrypt,
+            register: iv.try_into().unwrap(),
+            out: [0; BLOCK_SIZE],
+            pos: BLOCK_SIZE,
+        })
+    }
+
+    pub fn xor_key_stream(&mut self, dst: &mut [u8], src: &[u8]) -> Result<()> {
+        if dst.len() < src.len() {
+            return Err(Error::OutputTooSmall(src.len(), dst.len()));
+        }
+        let dst = &mut dst[..src.len()];
+        dst.copy_from_slice(src);
+        self.xor_key_stream_inplace(dst);
+        Ok(())
+    }
+
+    pub fn xor_key_stream_inplace(&mut self, in_out: &mut [u8]) {
+        match self.segment {
+            CFBSegment::Bit128 => self.cfb128(in_out),
+            CFBSegment::Bit8 => self.cfb8(in_out),
+            CFBSegment::Bit1 => self.cfb1(in_out),
+        }
+    }
+
+    fn cfb128(&mut self, in_out: &mut [u8]) {
+        for x in in_out {
+            if self.pos == BLOCK_SIZE {
+                self.block.encrypt(&mut self.out, &self.register);
+                self.pos = 0;
+            }
+            let c = if self.decrypt { *x } else { *x ^ self.out[self.pos] };
+            *x ^= self.out[self.pos];
+            self.register[self.pos] = c;
+            self.pos += 1;
+        }
+    }
+
+    fn cfb8(&mut self, in_out: &mut [u8]) {
+        for x in in_out {
+            self.block.encrypt(&mut self.out, &self.register);
+            let c = if self.decrypt { *x } else { *x ^ self.out[0] };
+            *x ^= self.out[0];
+            self.register.copy_within(1.., 0);
+            self.register[BLOCK_SIZE - 1] = c;
+        }
+    }
+
+    fn cfb1(&mut self, in_out: &mut [u8]) {
+        let mut register = u128::from_be_bytes(self.register);
+        for x in in_out {
+            for i in (0..8).rev() {
+                self.block.encrypt(&mut self.out, &register.to_be_bytes());
+                let k = self.out[0] >> 7;
+                let p = (*x >> i) & 1;
+                let c = if self.decrypt { p } else { p ^ k };
+                *x ^= k << i;
+                register = (register << 1) | c as u128;
+            }
+        }
+        self.register = register.to_be_bytes();
+    }
+}
+
+impl<B: Block> Drop for CFBMode<B> {
+    fn drop(&mut self) {
+        self.register.fill(0);
+        self.out.fill(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sm4::Cipher;
+    use hex_literal::hex;
+
+    const KEY: [u8; 16] = hex!("0123456789ABCDEFFEDCBA9876543210");
+    const IV: [u8; 16] = hex!("000102030405060708090A0B0C0D0E0F");
+
+    #[test]
+    fn test_cfb() {
+        let plain = hex!("AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDD");
+        let tests = [
+            // openssl enc -sm4-cfb
+            (CFBSegment::Bit128, hex!("ac3236cb970cc20791364c395a1342d12f1d1c833abb135086a6faa42f167242")),
+            // the same construction as openssl enc -aes-128-cfb8 and -aes-128-cfb1.
+            (CFBSegment::Bit8, hex!("ac18c95030be9a3f8c9d480612b672f82315f68d61f810c443056ad688b0fd26")),
+            (CFBSegment::Bit1, hex!("802ab4c04271b6a6bd0a412c89af2a8be20b092c8e58c65af4e985e04bd78f1b")),
+        ];
+        for (segment, wanted) in tests {
+            let mut out = [0; 32];
+            let mut cfb = CFBMode::new_encrypter(Cipher::new(&KEY), &IV, segment).unwrap();
+            cfb.xor_key_stream(&mut out, &plain).unwrap();
+            assert_eq!(out, wanted, "{:?}", segment);
+
+            let mut cfb = CFBMode::new_decrypter(Cipher::new(&KEY), &IV, segment).unwrap();
+            cfb.xor_key_stream_inplace(&mut out);
+            assert_eq!(out, plain, "{:?}", segment);
+
+            // streaming with the calls not aligned to the blocks.
+            for split in [1, 7, 16, 17, 31] {
+                let mut data = plain;
+                let mut cfb = CFBMode::new_encrypter(Cipher::new(&KEY), &IV, segment).unwrap();
+                let (a, b) = data.split_at_mut(split);
+                cfb.xor_key_stream_inplace(a);
+                cfb.xor_key_stream_inplace(b);
+                assert_eq!(data, wanted, "{:?} {}", segment, split);
+
+                let mut cfb = CFBMode::new_decrypter(Cipher::new(&KEY), &IV, segment).unwrap();
+                let (a, b) = data.split_at_mut(split);
+                cfb.xor_key_stream_inplace(a);
+                cfb.xor_key_stream_inplace(b);
+                assert_eq!(data, plain, "{:?} {}", segment, split);
+            }
+        }
+
+        assert!(matches!(CFBMode::new_encrypter(Cipher::new(&KEY), &IV[..8], CFBSegment::Bit8), Err(Error::InvalidNonceSize(16, 8))));
+    }
+}
@@ -0,0 +1,206 @@
+use super::{Error, Result};
+use crate::traits::Block;
+
+const BLOCK_SIZE: usize = 16;
+
+// The keystream is generated 16 blocks at a time, so that the block cipher
+// takes its widest path, e.g., sm4::Blocks' block16.
+const BATCH_BLOCKS: usize = 16;
+const BATCH_SIZE: usize = BATCH_BLOCKS * BLOCK_SIZE;
+
+// CTRMode is the counter mode of NIST SP 800-38A, a stream cipher.
+// The counter is the low-order `width` bits of the counter block, and wraps
+// around without touching the high-order bits, e.g., GCM uses a width of 32.
+pub struct CTRMode<B: Block> {
+    pub block: B,
+    iv: u128,
+    mask: u128,
+    // the counter block of keystream[0].
+    counter: u128,
+    keystream: [u8; BATCH_SIZE],
+    // keystream[pos..len] is not used yet.
+    pos: usize,
+    len: usize,
+}
+
+impl<B: Block> CTRMode<B> {
+    // Returns a CTR mode with a full 128-bit counter.
+    pub fn new(block: B, iv: &[u8]) -> Result<Self> {
+        Self::new_with_counter_width(block, iv, 128)
+    }
+
+    pub fn new_with_counter_width(block: B, iv: &[u8], width: u32) -> Result<Self> {
+        debug_assert_eq!(block.block_size(), BLOCK_SIZE);
+        if iv.len() != BLOCK_SIZE {
+            return Err(Error::InvalidNonceSize(BLOCK_SIZE, iv.len()));
+        }
+        if width == 0 || width > 128 {
+            return Err(Error::InvalidCounterWidth(width));
+        }
+        let iv = u128::from_be_bytes(iv.try_into().unwrap());
+        Ok(CTRMode {
+            block,
+            iv,
+            mask: u128::MAX >> (128 - width),
+            counter: iv,
+            keystream: [0; BATCH_SIZE],
+            pos: 0,
+            len: 0,
+        })
+    }
+
+    // Moves to the byte offset of the keystream, the next xor_key_stream starts there.
+    pub fn seek(&mut self, offset: u64) {
+        let blocks = (offset / BLOCK_SIZE as u64) as u128;
+        self.counter = add_counter(self.iv, blocks, self.mask);
+        self.len = 0;
+        self.pos = 0;
+        let skip = offset as usize % BLOCK_SIZE;
+        if skip != 0 {
+            self.refill(1);
+            self.pos = skip;
+        }
+    }
+
+    // XORs each byte of src with a byte of the keystream into dst.
+    pub fn xor_key_stream(&mut self, dst: &mut [u8], src: &[u8]) -> Result<()> {
+        if dst.len() < src.len() {
+            return Err(Error::OutputTooSmall(src.len(), dst.len()));
+        }
+        let mut done = 0;
+        while done < src.len() {
+            if self.pos == self.len {
+                self.refill((src.len() - done).div_ceil(BLOCK_SIZE));
+            }
+            let n = (self.len - self.pos).min(src.len() - done);
+            dst[done..done + n]
+                .iter_mut()
+                .zip(&src[done..done + n])
+                .zip(&self.keystream[self.pos..self.pos + n])
+                .for_each(|((z, x), k)| *z = *x ^ *k);
+            self.pos += n;
+            done += n;
+        }
+        Ok(())
+    }
+
+    pub fn xor_key_stream_inplace(&mut self, in_out: &mut [u8]) {
+        let mut done = 0;
+        while done < in_out.len() {
+            if self.pos == self.len {
+                self.refill((in_out.len() - done).div_ceil(BLOCK_SIZE));
+            }
+            let n = (self.len - self.pos).min(in_out.len() - done);
+            in_out[done..done + n]
+                .iter_mut()
+                .zip(&self.keystream[self.pos..self.pos + n])
+                .for_each(|(z, k)| *z ^= *k);
+            self.pos += n;
+            done += n;
+        }
+    }
+
+    // Generates the keystream of at most BATCH_BLOCKS blocks.
+    fn refill(&mut self, blocks: usize) {
+        let blocks = blocks.clamp(1, BATCH_BLOCKS);
+        let mut counter = self.counter;
+        for chunk in self.keystream[..blocks * BLOCK_SIZE].chunks_exact_mut(BLOCK_SIZE) {
+            chunk.copy_from_slice(&counter.to_be_bytes());
+            counter = add_counter(counter, 1, self.mask);
+        }
+        self.block.encrypt_inplace(&mut self.keystream[..blocks * BLOCK_SIZE]);
+        self.counter = counter;
+        self.pos = 0;
+        self.len = blocks * BLOCK_SIZE;
+    }
+}
+
+#[inline]
+fn add_counter(counter: u128, n: u128, mask: u128) -> u128 {
+    (counter & !mask) | (counter.wrapping_add(n) & mask)
+}
+
+impl<B: Block> Drop for CTRMode<B> {
+    fn drop(&mut self) {
+        self.keystream.fill(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sm4::Cipher;
+    use hex_literal::hex;
+
+    const KEY: [u8; 16] = hex!("0123456789ABCDEFFEDCBA9876543210");
+    const PLAIN: [u8; 64] = hex!("AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDDEEEEEEEEEEEEEEEEFFFFFFFFFFFFFFFFAAAAAAAAAAAAAAAABBBBBBBBBBBBBBBB");
+
+    #[test]
+    fn test_ctr() {
+        // draft-ribose-cfrg-sm4, A.2.5.1
+        let iv = hex!("000102030405060708090A0B0C0D0E0F");
+        let wanted = hex!("AC3236CB970CC20791364C395A1342D1A3CBC1878C6F30CD074CCE385CDD70C7F234BC0E24C11980FD1286310CE37B926E02FCD0FAA0BAF38B2933851D824514");
+
+        let mut ctr = CTRMode::new(Cipher::new(&KEY), &iv).unwrap();
+        let mut out = [0; 64];
+        ctr.xor_key_stream(&mut out, &PLAIN).unwrap();
+        assert_eq!(out, wanted);
+
+        // any split of the stream.
+        for split in [1, 15, 16, 17, 33, 63] {
+            let mut ctr = CTRMode::new(Cipher::new(&KEY), &iv).unwrap();
+            let mut data = PLAIN;
+            let (a, b) = data.split_at_mut(split);
+            ctr.xor_key_stream_inplace(a);
+            ctr.xor_key_stream_inplace(b);
+            assert_eq!(data, wanted);
+        }
+
+        let mut ctr = CTRMode::new(Cipher::new(&KEY), &iv).unwrap();
+        ctr.xor_key_stream_inplace(&mut out);
+        assert_eq!(out, PLAIN);
+    }
+
+    #[test]
+    fn test_ctr_counter_width() {
+        let iv = hex!("000102030405060708090A0BFFFFFFFE");
+        let mut ks = [0; 64];
+        let mut ctr = CTRMode::new_with_counter_width(Cipher::new(&KEY), &iv, 32).unwrap();
+        ctr.xor_key_stream_inplace(&mut ks);
+        assert_eq!(ks, hex!("e1b145bc04e75d311ac2eccfb92f52fb83c91f45987d37e3a18cec8c9ed04bb3aee26fea46d7ac0a03c4f48560557e53a1af29f378b4e8f05c2ae596b99753f6"));
+
+        let mut ks = [0; 64];
+        let mut ctr = CTRMode::new(Cipher::new(&KEY), &iv).unwrap();
+        ctr.xor_key_stream_inplace(&mut ks);
+        assert_eq!(ks, hex!("e1b145bc04e75d311ac2eccfb92f52fb83c91f45987d37e3a18cec8c9ed04bb312d101be29d84bbfa4a8803350f401161ab2c4abb6898a40683eaa75e01fafa1"));
+
+        // openssl enc -sm4-ctr wraps the 128-bit counter.
+        let iv = [0xff; 16];
+        let mut ks = [0; 32];
+        let mut ctr = CTRMode::new(Cipher::new(&KEY), &iv).unwrap();
+        ctr.xor_key_stream_inplace(&mut ks);
+        let mut zero = [0; 16];
+        CTRMode::new(Cipher::new(&KEY), &[0; 16]).unwrap().xor_key_stream_inplace(&mut zero);
+        assert_eq!(ks[16..], zero);
+
+        assert!(matches!(CTRMode::new_with_counter_width(Cipher::new(&KEY), &iv, 0), Err(Error::InvalidCounterWidth(0))));
+        assert!(matches!(CTRMode::new_with_counter_width(Cipher::new(&KEY), &iv, 129), Err(Error::InvalidCounterWidth(129))));
+        assert!(matches!(CTRMode::new(Cipher::new(&KEY), &iv[..12]), Err(Error::InvalidNonceSize(16, 12))));
+    }
+
+    #[test]
+    fn test_ctr_seek() {
+        let iv = hex!("000102030405060708090A0BFFFFFFF0");
+        let mut stream = [0u8; 1000];
+        let mut ctr = CTRMode::new_with_counter_width(Cipher::new(&KEY), &iv, 32).unwrap();
+        ctr.xor_key_stream_inplace(&mut stream);
+
+        for offset in [0, 1, 16, 255, 256, 257, 600, 999] {
+            ctr.seek(offset as u64);
+            let mut ks = [0u8; 300];
+            let n = ks.len().min(stream.len() - offset);
+            ctr.xor_key_stream_inplace(&mut ks[..n]);
+            assert_eq!(ks[..n], stream[offset..offset + n], "{}", offset);
+        }
+    }
+}
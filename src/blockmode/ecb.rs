@@ -0,0 +1,103 @@
+use alloc::vec::Vec;
+
+use super::padding::Padding;
+use super::{Error, Result};
+use crate::traits::Block;
+
+// ECBMode encrypts each block independently. It leaks the equal blocks of
+// the plaintext, and is only for interoperability, e.g., GM/T 0018 devices.
+pub struct ECBMode<B: Block> {
+    pub block_size: usize,
+    pub block: B,
+}
+
+impl<B: Block> ECBMode<B> {
+    pub fn new(block: B) -> Self {
+        ECBMode {
+            block_size: block.block_size(),
+            block,
+        }
+    }
+
+    // in_out must be a multiple of the block size.
+    pub fn encrypt_inplace(&self, in_out: &mut [u8]) -> Result<()> {
+        if in_out.len() % self.block_size != 0 {
+            return Err(Error::InvalidInputSize);
+        }
+        self.block.encrypt_inplace(in_out);
+        Ok(())
+    }
+
+    pub fn decrypt_inplace(&self, in_out: &mut [u8]) -> Result<()> {
+        if in_out.len() % self.block_size != 0 {
+            return Err(Error::InvalidInputSize);
+        }
+        self.block.decrypt_inplace(in_out);
+        Ok(())
+    }
+
+    pub fn encrypt_padded_vec(&self, data: &[u8], padding: Padding) -> Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(data.len() + self.block_size);
+        out.extend_from_slice(data);
+        padding.pad(&mut out, self.block_size);
+        self.encrypt_inplace(&mut out)?;
+        Ok(out)
+    }
+
+    pub fn decrypt_padded_vec(&self, data: &[u8], padding: Padding) -> Result<Vec<u8>> {
+        let mut out = data.to_vec();
+        self.decrypt_inplace(&mut out)?;
+        match padding.unpad(&out, self.block_size) {
+            Ok(n) => {
+                out.truncate(n);
+                Ok(out)
+            }
+            Err(e) => {
+                out.fill(0);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sm4::Cipher;
+    use hex_literal::hex;
+
+    const KEY: [u8; 16] = hex!("0123456789ABCDEFFEDCBA9876543210");
+
+    #[test]
+    fn test_ecb() {
+        // GB/T 32907-2016, A.1
+        let ecb = ECBMode::new(Cipher::new(&KEY));
+        let mut data = [KEY, KEY].concat();
+        ecb.encrypt_inplace(&mut data).unwrap();
+        assert_eq!(data, hex!("681EDF34D206965E86B3E94F536E4246681EDF34D206965E86B3E94F536E4246"));
+        ecb.decrypt_inplace(&mut data).unwrap();
+        assert_eq!(data, [KEY, KEY].concat());
+        assert!(matches!(ecb.encrypt_inplace(&mut data[..17]), Err(Error::InvalidInputSize)));
+    }
+
+    #[test]
+    fn test_ecb_padded() {
+        // openssl enc -sm4-ecb
+        let ecb = ECBMode::new(Cipher::new(&KEY));
+        let tests: [(usize, &[u8]); 3] = [
+            (0, &hex!("002a8a4efa863ccad024ac0300bb40d2")),
+            (20, &hex!("425ff88b82ac855280317fdc63321b0fb928bf5ee8994cf03ffe6846fe8e7b66")),
+            (32, &hex!("425ff88b82ac855280317fdc63321b0f425ff88b82ac855280317fdc63321b0f002a8a4efa863ccad024ac0300bb40d2")),
+        ];
+        for (n, wanted) in tests {
+            let plain = [b'a'; 32];
+            let ciphertext = ecb.encrypt_padded_vec(&plain[..n], Padding::PKCS7).unwrap();
+            assert_eq!(ciphertext, wanted);
+            assert_eq!(ecb.decrypt_padded_vec(&ciphertext, Padding::PKCS7).unwrap(), plain[..n]);
+        }
+
+        let ciphertext = ecb.encrypt_padded_vec(b"abc", Padding::ISO7816).unwrap();
+        assert!(matches!(ecb.decrypt_padded_vec(&ciphertext, Padding::PKCS7), Err(Error::InvalidPadding)));
+        assert!(matches!(ecb.decrypt_padded_vec(&ciphertext[..15], Padding::PKCS7), Err(Error::InvalidInputSize)));
+    }
+}
@@ -1,5 +1,6 @@
 mod ghash;
 mod ghash_generic;
+mod stream;
 
 #[cfg(target_arch = "aarch64")]
 mod ghash_aarch64;
@@ -7,19 +8,16 @@ mod ghash_aarch64;
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 mod ghash_amd64;
 
+pub use ghash::GHash;
+pub use stream::*;
 use ghash::*;
 
-#[cfg(target_arch = "aarch64")]
-use ghash_aarch64::*;
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-use ghash_amd64::*;
-use ghash_generic::GHasherGeneric;
-
+use super::ctr::CTRMode;
 use super::{Error, Result};
 use crate::sm4;
 use crate::traits::Block;
 use crate::traits::AEAD;
-use core::cmp::min;
+use subtle::ConstantTimeEq;
 
 const BLOCK_SIZE: usize = 16;
 const TAG_SIZE: usize = 16;
@@ -39,6 +37,7 @@ pub struct GCM<B: Block, const N: usize, const T: usize> {
     key: [u8; 16],
 }
 
+// GHASH(key, data1 || pad || data2 || pad || len(data1) || len(data2))
 #[inline(always)]
 fn ghash(
     tag: &mut [u8; 16],
@@ -46,58 +45,48 @@ fn ghash(
     data1: Option<&[u8]>,
     data2: &[u8],
 ) {
-    #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
-    ghash_template::<GHasherGeneric>(tag, key, data1, data2);
-
-    #[cfg(any(target_arch = "x86_64", target_arch = "x86"))]
-    if support_pmull_amd64() {
-        ghash_template::<GHasherAmd64>(tag, key, data1, data2);
-    }else{
-        ghash_template::<GHasherGeneric>(tag, key, data1, data2);
-    }
-
-    #[cfg(target_arch = "aarch64")]
-    if support_pmull_aarch64() {
-        // ghash_template::<GHasherGeneric>(tag, key, data1, data2);
-        ghash_template::<GHasherAarch64>(tag, key, data1, data2);
-    } else {
-        ghash_template::<GHasherGeneric>(tag, key, data1, data2);
-    };
-}
-
-#[inline(always)]
-fn ghash_template<H: GHash + Default>(
-    tag: &mut [u8; 16],
-    key: &[u8; 16],
-    data1: Option<&[u8]>,
-    data2: &[u8],
-) {
-    let mut g = H::default();
-    g.init(key);
+    let mut g = GHash::new(key);
     let mut a = 0;
     if let Some(data1) = data1 {
         g.update(data1);
+        g.pad();
         a = data1.len() as u64 * 8;
     }
 
     g.update(data2);
-    g.update_u64x2(a, data2.len() as u64 * 8);
-    g.sum(tag);
+    g.pad();
+    g.update(&lengths_block(a, data2.len() as u64 * 8));
+    *tag = g.finalize();
+}
+
+#[inline]
+fn lengths_block(a: u64, b: u64) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block[..8].copy_from_slice(&a.to_be_bytes());
+    block[8..].copy_from_slice(&b.to_be_bytes());
+    block
 }
 
 // gcm represents a Galois Counter Mode with a specific key. See
 // https://csrc.nist.gov/groups/ST/toolkit/BCM/documents/proposedmodes/gcm/gcm-revised-spec.pdf
 impl<B: Block, const N: usize, const T: usize> GCM<B, N, T> {
+    // Panics if N or T is invalid, see try_new.
     pub fn new(block: B) -> Self {
-        debug_assert!(T >= MIN_TAG_SIZE);
-        debug_assert!(T <= BLOCK_SIZE);
-        debug_assert_eq!(block.block_size(), BLOCK_SIZE);
+        Self::try_new(block).unwrap()
+    }
 
-        // h = CIPH_K(0^128)
-        let mut key: [u8; 16] = [0; BLOCK_SIZE];
-        block.encrypt_inplace(&mut key);
+    // The nonce size N is non-zero, and the tag size T is 12..=16.
+    pub fn try_new(block: B) -> Result<Self> {
+        check_sizes(N, T, false)?;
+        debug_assert_eq!(block.block_size(), BLOCK_SIZE);
+        let key = hash_key(&block);
+        Ok(GCM { cipher: block, key })
+    }
+}
 
-        GCM { cipher: block, key }
+impl<B: Block, const N: usize, const T: usize> Drop for GCM<B, N, T> {
+    fn drop(&mut self) {
+        self.key.fill(0);
     }
 }
 
@@ -122,16 +111,7 @@ impl<B: Block, const N: usize, const T: usize> AEAD for GCM<B, N, T> {
         if nonce.len() != N {
             return Err(Error::InvalidNonceSize(N, nonce.len()));
         }
-
-        let mut counter = self.derive_counter(nonce);
-        let mut tag_mask = [0; BLOCK_SIZE];
-        self.cipher.encrypt(&mut tag_mask, &counter);
-        inc32(&mut counter);
-
-        self.counter_crypt_inplacd(in_out, &mut counter);
-
-        self.auth(tag, in_out, add, &tag_mask);
-        Ok(())
+        seal_inplace(&self.cipher, &self.key, T, in_out, tag, nonce, add)
     }
 
     fn open_inplace(
@@ -144,23 +124,7 @@ impl<B: Block, const N: usize, const T: usize> AEAD for GCM<B, N, T> {
         if nonce.len() != N {
             return Err(Error::GCMAuthenticationError);
         }
-
-        let mut tag_mask = [0; BLOCK_SIZE];
-        let mut counter = self.derive_counter(nonce);
-        self.cipher.encrypt(&mut tag_mask, &counter);
-
-        inc32(&mut counter);
-
-        let mut expected_tag = [0; BLOCK_SIZE];
-        self.auth(&mut expected_tag, in_out, add, &tag_mask);
-
-        self.counter_crypt_inplacd(in_out, &mut counter);
-        for i in 0..min(T, tag.len()) {
-            if expected_tag[i] != tag[i] {
-                return Err(Error::GCMAuthenticationError);
-            }
-        }
-        Ok(())
+        open_inplace(&self.cipher, &self.key, T, in_out, tag, nonce, add)
     }
 
     fn seal(
@@ -170,25 +134,7 @@ impl<B: Block, const N: usize, const T: usize> AEAD for GCM<B, N, T> {
         plaintext: &[u8],
         add: Option<&[u8]>,
     ) -> Result<()> {
-        if nonce.len() != N {
-            return Err(Error::InvalidNonceSize(N, nonce.len()));
-        }
-
-        let plaintext_length = plaintext.len();
-        if out.len() < plaintext_length + T {
-            return Err(Error::OutputTooSmall(plaintext_length + T, out.len()));
-        }
-
-        let (ciphertext, tag) = out.split_at_mut(plaintext_length);
-        let mut counter = self.derive_counter(nonce);
-        let mut tag_mask = [0; BLOCK_SIZE];
-        self.cipher.encrypt(&mut tag_mask, &counter);
-        inc32(&mut counter);
-
-        self.counter_crypt(ciphertext, plaintext, &mut counter);
-
-        self.auth(tag, ciphertext, add, &tag_mask);
-        Ok(())
+        seal(self, out, nonce, plaintext, add)
     }
 
     fn open(
@@ -198,134 +144,283 @@ impl<B: Block, const N: usize, const T: usize> AEAD for GCM<B, N, T> {
         ciphertext: &[u8],
         add: Option<&[u8]>,
     ) -> Result<usize> {
-        if nonce.len() != N {
-            return Err(Error::GCMAuthenticationError);
-        }
+        open(self, out, nonce, ciphertext, add)
+    }
+}
 
-        if ciphertext.len() < T {
-            return Err(Error::GCMCiphertextTooSmall(ciphertext.len(), T));
-        }
+pub type Sm4DynGcm = DynGCM<sm4::Cipher>;
 
-        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - T);
-        if out.len() < ciphertext.len() {
-            return Err(Error::OutputBufferTooShort(
-                ciphertext.len(),
-                out.len(),
-            ));
-        }
+// Returns a GCM instance with the nonce size and the tag size chosen at runtime.
+// The key must have length 16.
+pub fn new_sm4_dyn_gcm(key: &[u8], nonce_size: usize, tag_size: usize) -> Result<Sm4DynGcm> {
+    if key.len() != sm4::KEY_SIZE {
+        return Err(Error::InvalidKeySize(sm4::KEY_SIZE, key.len()));
+    }
+    DynGCM::new(sm4::Cipher::new(key), nonce_size, tag_size)
+}
 
-        let mut tag_mask = [0; BLOCK_SIZE];
-        let mut counter = self.derive_counter(nonce);
-        self.cipher.encrypt(&mut tag_mask, &counter);
+// DynGCM is GCM with the nonce size and the tag size as runtime parameters,
+// e.g., for the peers sending 16-byte nonces.
+pub struct DynGCM<B: Block> {
+    pub cipher: B,
+    key: [u8; 16],
+    nonce_size: usize,
+    tag_size: usize,
+}
 
-        inc32(&mut counter);
+impl<B: Block> DynGCM<B> {
+    // The nonce size is non-zero, and the tag size is 12..=16.
+    pub fn new(block: B, nonce_size: usize, tag_size: usize) -> Result<Self> {
+        Self::new_with(block, nonce_size, tag_size, false)
+    }
 
-        let mut expected_tag = [0; BLOCK_SIZE];
-        self.auth(&mut expected_tag, ciphertext, add, &tag_mask);
+    // Allows the 4 and 8-byte tags as well. NIST SP 800-38D, Appendix C limits
+    // the length of the messages and the number of the invocations with a key
+    // for such tags, the caller takes care of that.
+    pub fn new_with_short_tag(block: B, nonce_size: usize, tag_size: usize) -> Result<Self> {
+        Self::new_with(block, nonce_size, tag_size, true)
+    }
 
-        for i in 0..T {
-            if expected_tag[i] != tag[i] {
-                return Err(Error::GCMAuthenticationError);
-            }
-        }
+    fn new_with(block: B, nonce_size: usize, tag_size: usize, allow_short_tag: bool) -> Result<Self> {
+        check_sizes(nonce_size, tag_size, allow_short_tag)?;
+        debug_assert_eq!(block.block_size(), BLOCK_SIZE);
+        let key = hash_key(&block);
+        Ok(DynGCM { cipher: block, key, nonce_size, tag_size })
+    }
+}
 
-        self.counter_crypt(out, ciphertext, &mut counter);
-        Ok(ciphertext.len())
+impl<B: Block> Drop for DynGCM<B> {
+    fn drop(&mut self) {
+        self.key.fill(0);
     }
 }
 
-impl<B: Block, const N: usize, const T: usize> GCM<B, N, T> {
-    fn derive_counter(&self, nonce: &[u8]) -> [u8; 16] {
-        let mut counter = [0; BLOCK_SIZE];
-        if nonce.len() == STD_NONCE_SIZE {
-            counter[..STD_NONCE_SIZE].copy_from_slice(nonce);
-            counter[BLOCK_SIZE - 1] = 1;
-        } else {
-            ghash(&mut counter, &self.key, None, nonce);
-        }
-        counter
+impl<B: Block> AEAD for DynGCM<B> {
+    type Error = super::Error;
+
+    fn overhead(&self) -> usize {
+        self.tag_size
     }
 
-    // counterCrypt crypts in to out using g.cipher in counter mode.
-    fn counter_crypt(
+    fn nonce_size(&self) -> usize {
+        self.nonce_size
+    }
+
+    fn seal_inplace(
         &self,
-        out: &mut [u8],
-        input: &[u8],
-        counter: &mut [u8; BLOCK_SIZE],
-    ) {
-        let counter_buf_len = (input.len() + BLOCK_SIZE) & (!15);
-        let mut counter_buf = vec![0; counter_buf_len];
-
-        for chunk in counter_buf.chunks_exact_mut(BLOCK_SIZE) {
-            chunk.copy_from_slice(counter);
-            inc32(counter);
+        in_out: &mut [u8],
+        tag: &mut [u8],
+        nonce: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<()> {
+        if nonce.len() != self.nonce_size {
+            return Err(Error::InvalidNonceSize(self.nonce_size, nonce.len()));
         }
-        self.cipher.encrypt_inplace(&mut counter_buf);
-
-        input
-            .iter()
-            .zip(&counter_buf[..input.len()])
-            .zip(out.iter_mut())
-            .for_each(|((x, y), z)| *z = *x ^ *y);
+        seal_inplace(&self.cipher, &self.key, self.tag_size, in_out, tag, nonce, add)
     }
 
-    // counterCrypt crypts in to out using g.cipher in counter mode.
-    fn counter_crypt_inplacd(
+    fn open_inplace(
         &self,
         in_out: &mut [u8],
-        counter: &mut [u8; BLOCK_SIZE],
-    ) {
-        let in_out_length = in_out.len();
-        let counter_buf_len = (in_out_length + BLOCK_SIZE) & (!15);
-        let mut counter_buf = vec![0; counter_buf_len];
-
-        for chunk in counter_buf.chunks_exact_mut(BLOCK_SIZE) {
-            chunk.copy_from_slice(counter);
-            inc32(counter);
+        tag: &[u8],
+        nonce: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<()> {
+        if nonce.len() != self.nonce_size {
+            return Err(Error::GCMAuthenticationError);
         }
-        self.cipher.encrypt_inplace(&mut counter_buf);
+        open_inplace(&self.cipher, &self.key, self.tag_size, in_out, tag, nonce, add)
+    }
 
-        in_out
-            .iter_mut()
-            .zip(&counter_buf[..in_out_length])
-            .for_each(|(z, y)| *z ^= *y);
+    fn seal(
+        &self,
+        out: &mut [u8],
+        nonce: &[u8],
+        plaintext: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<()> {
+        seal(self, out, nonce, plaintext, add)
     }
 
-    // auth calculates GHASH(ciphertext, additionalData), masks the result with
-    // tagMask and writes the result to out.
-    fn auth(
+    fn open(
         &self,
-        tag: &mut [u8],
+        out: &mut [u8],
+        nonce: &[u8],
         ciphertext: &[u8],
         add: Option<&[u8]>,
-        tag_mask: &[u8; BLOCK_SIZE],
-    ) {
-        let mut out = [0; 16];
-        ghash(&mut out, &self.key, add, ciphertext);
+    ) -> Result<usize> {
+        open(self, out, nonce, ciphertext, add)
+    }
+}
+
+fn check_sizes(nonce_size: usize, tag_size: usize, allow_short_tag: bool) -> Result<()> {
+    if nonce_size == 0 {
+        return Err(Error::InvalidNonceSize(STD_NONCE_SIZE, 0));
+    }
+    let short = allow_short_tag && (tag_size == 4 || tag_size == 8);
+    if !short && !(MIN_TAG_SIZE..=TAG_SIZE).contains(&tag_size) {
+        return Err(Error::InvalidTagSize(tag_size));
+    }
+    Ok(())
+}
+
+// h = CIPH_K(0^128)
+fn hash_key<B: Block>(block: &B) -> [u8; BLOCK_SIZE] {
+    let mut key = [0; BLOCK_SIZE];
+    block.encrypt_inplace(&mut key);
+    key
+}
+
+// deriveCounter computes the initial GCM counter state from the given nonce.
+// See NIST SP 800-38D, section 7.1.
+fn derive_counter(key: &[u8; BLOCK_SIZE], nonce: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut counter = [0; BLOCK_SIZE];
+    if nonce.len() == STD_NONCE_SIZE {
+        counter[..STD_NONCE_SIZE].copy_from_slice(nonce);
+        counter[BLOCK_SIZE - 1] = 1;
+    } else {
+        ghash(&mut counter, key, None, nonce);
+    }
+    counter
+}
+
+// Returns the tag mask E(J_0), and the CTR starting at inc32(J_0).
+fn start<'a, B: Block>(
+    cipher: &'a B,
+    key: &[u8; BLOCK_SIZE],
+    nonce: &[u8],
+) -> ([u8; BLOCK_SIZE], CTRMode<&'a B>) {
+    let mut counter = derive_counter(key, nonce);
+    let mut tag_mask = [0; BLOCK_SIZE];
+    cipher.encrypt(&mut tag_mask, &counter);
+    inc32(&mut counter);
+    let ctr = CTRMode::new_with_counter_width(cipher, &counter, 32).unwrap();
+    (tag_mask, ctr)
+}
 
-        let tag_length = tag.len();
-        out.iter_mut()
-            .zip(&tag_mask[..tag_length])
-            .for_each(|(z, x)| *z ^= *x);
-        tag.copy_from_slice(&out[..T]);
+// The nonce size is checked by the caller.
+fn seal_inplace<B: Block>(
+    cipher: &B,
+    key: &[u8; BLOCK_SIZE],
+    tag_size: usize,
+    in_out: &mut [u8],
+    tag: &mut [u8],
+    nonce: &[u8],
+    add: Option<&[u8]>,
+) -> Result<()> {
+    if tag.len() < tag_size {
+        return Err(Error::OutputTooSmall(tag_size, tag.len()));
     }
+    let (tag_mask, mut ctr) = start(cipher, key, nonce);
+    ctr.xor_key_stream_inplace(in_out);
+    let t = auth(key, in_out, add, &tag_mask);
+    tag[..tag_size].copy_from_slice(&t[..tag_size]);
+    Ok(())
 }
+
+// The tag is verified in constant time before decrypting.
+fn open_inplace<B: Block>(
+    cipher: &B,
+    key: &[u8; BLOCK_SIZE],
+    tag_size: usize,
+    in_out: &mut [u8],
+    tag: &[u8],
+    nonce: &[u8],
+    add: Option<&[u8]>,
+) -> Result<()> {
+    if tag.len() != tag_size {
+        return Err(Error::GCMAuthenticationError);
+    }
+    let (tag_mask, mut ctr) = start(cipher, key, nonce);
+    let expected_tag = auth(key, in_out, add, &tag_mask);
+    if expected_tag[..tag_size].ct_eq(tag).unwrap_u8() != 1 {
+        return Err(Error::GCMAuthenticationError);
+    }
+    ctr.xor_key_stream_inplace(in_out);
+    Ok(())
+}
+
+// seal and open on top of the in-place ones, for both GCM and DynGCM.
+fn seal<A: AEAD<Error = Error>>(
+    aead: &A,
+    out: &mut [u8],
+    nonce: &[u8],
+    plaintext: &[u8],
+    add: Option<&[u8]>,
+) -> Result<()> {
+    if nonce.len() != aead.nonce_size() {
+        return Err(Error::InvalidNonceSize(aead.nonce_size(), nonce.len()));
+    }
+    let (plaintext_length, tag_size) = (plaintext.len(), aead.overhead());
+    if out.len() < plaintext_length + tag_size {
+        return Err(Error::OutputTooSmall(plaintext_length + tag_size, out.len()));
+    }
+
+    let (ciphertext, tag) = out.split_at_mut(plaintext_length);
+    ciphertext.copy_from_slice(plaintext);
+    aead.seal_inplace(ciphertext, &mut tag[..tag_size], nonce, add)
+}
+
+fn open<A: AEAD<Error = Error>>(
+    aead: &A,
+    out: &mut [u8],
+    nonce: &[u8],
+    ciphertext: &[u8],
+    add: Option<&[u8]>,
+) -> Result<usize> {
+    if nonce.len() != aead.nonce_size() {
+        return Err(Error::GCMAuthenticationError);
+    }
+    let tag_size = aead.overhead();
+    if ciphertext.len() < tag_size {
+        return Err(Error::GCMCiphertextTooSmall(ciphertext.len(), tag_size));
+    }
+
+    let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - tag_size);
+    if out.len() < ciphertext.len() {
+        return Err(Error::OutputBufferTooShort(
+            ciphertext.len(),
+            out.len(),
+        ));
+    }
+    let out = &mut out[..ciphertext.len()];
+    out.copy_from_slice(ciphertext);
+    if let Err(e) = aead.open_inplace(out, tag, nonce, add) {
+        out.fill(0);
+        return Err(e);
+    }
+    Ok(ciphertext.len())
+}
+
+// auth calculates GHASH(ciphertext, additionalData), masks the result with
+// tagMask and returns it.
+fn auth(
+    key: &[u8; BLOCK_SIZE],
+    ciphertext: &[u8],
+    add: Option<&[u8]>,
+    tag_mask: &[u8; BLOCK_SIZE],
+) -> [u8; BLOCK_SIZE] {
+    let mut out = [0; BLOCK_SIZE];
+    ghash(&mut out, key, add, ciphertext);
+    out.iter_mut()
+        .zip(tag_mask)
+        .for_each(|(z, x)| *z ^= *x);
+    out
+}
+
 #[inline]
+// The rightmost 32 bits are incremented mod 2^32, J_0 derived from a nonce
+// other than 12 bytes may end with ffffffff.
 fn inc32(counter: &mut [u8]) {
     let clen = counter.len();
     let ctr = &mut counter[clen - 4..];
-    let x = u32::from_be_bytes(ctr.try_into().unwrap()) + 1;
+    let x = u32::from_be_bytes(ctr.try_into().unwrap()).wrapping_add(1);
     ctr.copy_from_slice(&u32::to_be_bytes(x));
 }
 
 #[cfg(test)]
 mod tests {
-    use super::new_sm4_gcm_std;
-    use crate::{
-        blockmode::gcm::GCM,
-        sm4::{self},
-        traits::AEAD,
-    };
+    use super::*;
     use hex_literal::hex;
     use std::vec::Vec;
 
@@ -449,6 +544,84 @@ mod tests {
         // assert_eq!(plain, hex!("7B5BD9FDAE2521A3F0FBDD2F4427142F785C52080B0DB22523C3BC5D8716D141CE315586EBB3EDF4480193B1B3C33524"));
     }
 
+    #[test]
+    fn test_dyn_gcm() {
+        let key = hex!("0123456789ABCDEFFEDCBA9876543210");
+        let add = b"AAAAAAAAAAAAAAAABBBB";
+        let plain = b"plaintext of the dynamic GCM";
+
+        // openssl mac -cipher SM4-GCM GMAC, the tag of an empty plaintext.
+        let nonce = hex!("000102030405060708090A0B0C0D0E0F10");
+        let g = new_sm4_dyn_gcm(&key, 17, 16).unwrap();
+        let mut tag = [0; 16];
+        g.seal_inplace(&mut [], &mut tag, &nonce, Some(add)).unwrap();
+        assert_eq!(tag, hex!("1F3B91F11EA7AEA74450ECC7F2902C28"));
+
+        // the same as the const parameters, the tag is truncated.
+        let nonce = &nonce[..16];
+        let fixed = GCM::<_, 16, 16>::new(sm4::Cipher::new(&key));
+        let mut wanted = [0; 28 + 16];
+        fixed.seal(&mut wanted, nonce, plain, Some(add)).unwrap();
+        for (tag_size, short) in [(12, false), (16, false), (8, true), (4, true)] {
+            let g = if short {
+                DynGCM::new_with_short_tag(sm4::Cipher::new(&key), 16, tag_size).unwrap()
+            } else {
+                new_sm4_dyn_gcm(&key, 16, tag_size).unwrap()
+            };
+            assert_eq!(g.overhead(), tag_size);
+            let mut out = vec![0; 28 + tag_size];
+            g.seal(&mut out, nonce, plain, Some(add)).unwrap();
+            assert_eq!(out, wanted[..28 + tag_size]);
+
+            let mut decrypted = [0; 28];
+            assert_eq!(g.open(&mut decrypted, nonce, &out, Some(add)).unwrap(), 28);
+            assert_eq!(&decrypted, plain);
+
+            out[0] ^= 1;
+            assert!(matches!(g.open(&mut decrypted, nonce, &out, Some(add)), Err(Error::GCMAuthenticationError)));
+            assert_eq!(decrypted, [0; 28]);
+            assert!(matches!(g.open(&mut decrypted, &nonce[..12], &out, Some(add)), Err(Error::GCMAuthenticationError)));
+        }
+    }
+
+    #[test]
+    fn test_inc32() {
+        let mut counter = hex!("0102030405060708090a0b0c000000ff");
+        inc32(&mut counter);
+        assert_eq!(counter, hex!("0102030405060708090a0b0c00000100"));
+        let mut counter = hex!("0102030405060708090a0b0cffffffff");
+        inc32(&mut counter);
+        assert_eq!(counter, hex!("0102030405060708090a0b0c00000000"));
+    }
+
+    #[test]
+    fn test_gcm_parameters() {
+        let key = hex!("0123456789ABCDEFFEDCBA9876543210");
+        for tag_size in [0, 4, 8, 11, 17] {
+            assert!(matches!(new_sm4_dyn_gcm(&key, 12, tag_size), Err(Error::InvalidTagSize(n)) if n == tag_size));
+        }
+        for tag_size in [0, 6, 10, 17] {
+            assert!(matches!(
+                DynGCM::new_with_short_tag(sm4::Cipher::new(&key), 12, tag_size),
+                Err(Error::InvalidTagSize(n)) if n == tag_size
+            ));
+        }
+        assert!(matches!(new_sm4_dyn_gcm(&key, 0, 16), Err(Error::InvalidNonceSize(12, 0))));
+        assert!(matches!(new_sm4_dyn_gcm(&key[..15], 12, 16), Err(Error::InvalidKeySize(16, 15))));
+        assert!(matches!(new_sm4_dyn_gcm(&[0; 32], 12, 16), Err(Error::InvalidKeySize(16, 32))));
+        assert!(matches!(GCM::<_, 12, 8>::try_new(sm4::Cipher::new(&key)), Err(Error::InvalidTagSize(8))));
+        assert!(matches!(GCM::<_, 0, 16>::try_new(sm4::Cipher::new(&key)), Err(Error::InvalidNonceSize(12, 0))));
+        assert!(GCM::<_, 16, 12>::try_new(sm4::Cipher::new(&key)).is_ok());
+
+        // the tag must have the exact size.
+        let g = new_sm4_gcm_std(&key);
+        let mut data = *b"plaintext";
+        let mut tag = [0; 16];
+        g.seal_inplace(&mut data, &mut tag, &[0; 12], None).unwrap();
+        assert!(matches!(g.open_inplace(&mut data, &tag[..12], &[0; 12], None), Err(Error::GCMAuthenticationError)));
+        assert!(matches!(g.seal_inplace(&mut data, &mut tag[..12], &[0; 12], None), Err(Error::OutputTooSmall(16, 12))));
+    }
+
     use std::time::*;
 
     // cargo test --release --package opengm_crypto --lib -- blockmode::gcm::tests::test_bench --exact --show-output
@@ -1,4 +1,10 @@
-pub trait GHash{
+use super::ghash_generic::GHasherGeneric;
+#[cfg(target_arch = "aarch64")]
+use super::ghash_aarch64::*;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use super::ghash_amd64::*;
+
+pub trait GHasher {
     fn init(&mut self, key: &[u8; 16]);
     fn reset(&mut self);
 
@@ -20,3 +26,156 @@ pub trait GHash{
 // ....
 // x^126 = (b15 >> 1) & 1
 // x^127 = b15 & 1
+
+// GHash is the universal hash of GCM over GF(2^128), with the key H.
+// The data is hashed as a whole, pad completes the partial block with zeros
+// so that each field of GCM starts at a block boundary, e.g.,
+// update(A), pad(), update(C), pad(), update(len(A) || len(C)), finalize().
+#[derive(Clone)]
+pub struct GHash {
+    hasher: Hasher,
+    buf: [u8; 16],
+    pos: usize,
+}
+
+// The backend is chosen by the CPU features once in GHash::new.
+#[derive(Clone)]
+enum Hasher {
+    Generic(GHasherGeneric),
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Amd64(GHasherAmd64),
+    #[cfg(target_arch = "aarch64")]
+    Aarch64(GHasherAarch64),
+}
+
+macro_rules! dispatch {
+    ($hasher:expr, $g:ident => $e:expr) => {
+        match $hasher {
+            Hasher::Generic($g) => $e,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Hasher::Amd64($g) => $e,
+            #[cfg(target_arch = "aarch64")]
+            Hasher::Aarch64($g) => $e,
+        }
+    };
+}
+
+impl GHash {
+    pub fn new(key: &[u8; 16]) -> Self {
+        let mut hasher = Hasher::new();
+        dispatch!(&mut hasher, g => g.init(key));
+        GHash { hasher, buf: [0; 16], pos: 0 }
+    }
+
+    // reset GHash for next computation, use the same key.
+    pub fn reset(&mut self) {
+        dispatch!(&mut self.hasher, g => g.reset());
+        self.buf.fill(0);
+        self.pos = 0;
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        if self.pos > 0 {
+            let n = (16 - self.pos).min(data.len());
+            self.buf[self.pos..self.pos + n].copy_from_slice(&data[..n]);
+            self.pos += n;
+            data = &data[n..];
+            if self.pos < 16 {
+                return;
+            }
+            dispatch!(&mut self.hasher, g => g.update(&self.buf));
+            self.pos = 0;
+        }
+        let full_blocks = data.len() & !15;
+        if full_blocks > 0 {
+            dispatch!(&mut self.hasher, g => g.update(&data[..full_blocks]));
+        }
+        let rest = &data[full_blocks..];
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.pos = rest.len();
+    }
+
+    // Pads the partial block with zeros, nothing if the data is aligned.
+    pub fn pad(&mut self) {
+        if self.pos > 0 {
+            // the backend pads a partial block.
+            dispatch!(&mut self.hasher, g => g.update(&self.buf[..self.pos]));
+            self.pos = 0;
+        }
+    }
+
+    // Pads the data and returns the hash.
+    pub fn finalize(mut self) -> [u8; 16] {
+        self.pad();
+        let mut h = [0u8; 16];
+        dispatch!(&self.hasher, g => g.sum(&mut h));
+        h
+    }
+}
+
+// The backends wipe H and y in their own drop.
+impl Drop for GHash {
+    fn drop(&mut self) {
+        self.buf.fill(0);
+    }
+}
+
+impl Hasher {
+    fn new() -> Self {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        if support_pmull_amd64() {
+            return Hasher::Amd64(GHasherAmd64::default());
+        }
+        #[cfg(target_arch = "aarch64")]
+        if support_pmull_aarch64() {
+            return Hasher::Aarch64(GHasherAarch64::default());
+        }
+        Hasher::Generic(GHasherGeneric::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_ghash() {
+        // from the tag of AES-128-GCM with a zero key and a zero nonce:
+        // GHASH = tag ^ E(J_0).
+        let key = hex!("66e94bd4ef8a2c3b884cfa59ca342b2e");
+        let a = b"AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCC";
+        let c = hex!("0389d8cd64b3a595fb21c8b27dbff077e784b8b85d5e4f34efe493e48896dfff002333026a");
+        let lengths = hex!("00000000000001200000000000000128");
+        let wanted = hex!("5a7ba76f938d18800ce3a326f93f15e1");
+
+        let mut g = GHash::new(&key);
+        g.update(a);
+        g.pad();
+        g.update(&c);
+        g.pad();
+        g.update(&lengths);
+        assert_eq!(g.finalize(), wanted);
+
+        // byte by byte.
+        let mut g = GHash::new(&key);
+        a.iter().for_each(|b| g.update(&[*b]));
+        g.pad();
+        c.iter().for_each(|b| g.update(&[*b]));
+        g.pad();
+        g.pad();
+        g.update(&lengths);
+        assert_eq!(g.clone().finalize(), wanted);
+
+        g.reset();
+        g.update(&[a.as_slice(), &[0; 12], &c, &[0; 11], &lengths].concat());
+        assert_eq!(g.finalize(), wanted);
+
+        // the generic backend, whatever the CPU.
+        let mut generic = GHasherGeneric::default();
+        generic.init(&key);
+        let mut g = GHash { hasher: Hasher::Generic(generic), buf: [0; 16], pos: 0 };
+        g.update(&[a.as_slice(), &[0; 12], &c, &[0; 11], &lengths].concat());
+        assert_eq!(g.finalize(), wanted);
+    }
+}
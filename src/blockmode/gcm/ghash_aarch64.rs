@@ -1,7 +1,7 @@
 use super::*;
 use core::arch::aarch64::*;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct GHasherAarch64 {
     h: u128, // the key, reflected
     y: u128,
@@ -15,7 +15,14 @@ pub fn support_pmull_aarch64() -> bool {
 
 }
 
-impl GHash for GHasherAarch64 {
+impl Drop for GHasherAarch64 {
+    fn drop(&mut self) {
+        self.h = 0;
+        self.y = 0;
+    }
+}
+
+impl GHasher for GHasherAarch64 {
     #[inline]
     fn init(&mut self, key: &[u8; 16]) {
         self.h = u128::from_be_bytes(*key);
@@ -14,7 +14,7 @@ pub fn support_pmull_aarch64() -> bool {
     is_aarch64_feature_detected!("neon") && is_aarch64_feature_detected!("aes")
 }
 
-impl GHash for GHasherAarch64 {
+impl GHasher for GHasherAarch64 {
     #[inline]
     fn init(&mut self, key: &[u8; 16]) {
         self.h = to_u128(key);
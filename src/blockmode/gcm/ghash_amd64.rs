@@ -1,8 +1,9 @@
-use super::GHash;
+use super::GHasher;
 use super::*;
 use core::arch::x86_64::*;
 use core::mem::transmute;
 
+#[derive(Clone)]
 pub struct GHasherAmd64 {
     h: __m128i, // the key
     y: __m128i,
@@ -58,7 +59,14 @@ impl Default for GHasherAmd64 {
     }
 }
 
-impl GHash for GHasherAmd64 {
+impl Drop for GHasherAmd64 {
+    fn drop(&mut self) {
+        self.h = ZERO;
+        self.y = ZERO;
+    }
+}
+
+impl GHasher for GHasherAmd64 {
     #[inline]
     fn init(&mut self, key: &[u8; 16]) {
         // key in reflected mode
@@ -1,4 +1,4 @@
-use super::GHash;
+use super::GHasher;
 use core::arch::x86_64::*;
 use core::mem::transmute;
 use std::simd::u64x2;
@@ -49,7 +49,7 @@ impl Default for GHasherAmd64 {
     }
 }
 
-impl GHash for GHasherAmd64 {
+impl GHasher for GHasherAmd64 {
     #[inline]
     fn init(&mut self, key: &[u8; 16]) {
         self.h = unsafe { reflect_xmm(transmute(*key)) };
@@ -1,7 +1,7 @@
 use super::*;
 use core::ops::{Add, AddAssign};
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct GHasherGeneric {
     // productTable contains the first sixteen powers of the key, H.
     // However, they are in bit reversed order. See NewGCMWithNonceSize.
@@ -10,6 +10,13 @@ pub struct GHasherGeneric {
     y: FieldElement,
 }
 
+impl Drop for GHasherGeneric {
+    fn drop(&mut self) {
+        self.product_table.fill(FieldElement::default());
+        self.y = FieldElement::default();
+    }
+}
+
 // reverse order of bits of f(x) * (x^7 + x^2 + x + 1) for deg(x) <= 3.
 // The result is represented by two bytes.
 // EX: f(x) = 1 and x^7 + x^2 + x + 1 = 0b0000_0000_1000_0111 => 0b1110_0001_0000_0000 = 0xe100
@@ -20,7 +27,7 @@ const GCM_REDUCTION_TABLE: [u64; 16] = [
     0xfd20, 0xd940, 0xc560, 0x9180, 0x8da0, 0xa9c0, 0xb5e0,
 ];
 
-impl GHash for GHasherGeneric {
+impl GHasher for GHasherGeneric {
     fn init(&mut self, key: &[u8; 16]) {
         // We precompute 16 multiples of |key|. However, when we do lookups
         // into this table we'll be using bits from a field element and
@@ -0,0 +1,261 @@
+use alloc::vec::Vec;
+use super::*;
+
+// NIST SP 800-38D: len(P) <= 2^39 - 256 bits.
+const MAX_DATA_SIZE: u64 = (1 << 36) - 32;
+
+impl<B: Block, const N: usize, const T: usize> GCM<B, N, T> {
+    // Returns an incremental encryptor, for the messages too big to be in memory.
+    pub fn encryptor(&self, nonce: &[u8]) -> Result<GCMEncryptor<'_, B, T>> {
+        Ok(GCMEncryptor { s: GCMStream::new(self, nonce)? })
+    }
+
+    // Returns an incremental decryptor, for the messages too big to be in memory.
+    pub fn decryptor(&self, nonce: &[u8]) -> Result<GCMDecryptor<'_, B, T>> {
+        Ok(GCMDecryptor {
+            s: GCMStream::new(self, nonce)?,
+            buffer: Vec::new(),
+        })
+    }
+}
+
+// The state shared by the encryptor and the decryptor, the GHASH is over
+// the additional data and the ciphertext.
+struct GCMStream<'a, B: Block> {
+    ctr: CTRMode<&'a B>,
+    ghash: GHash,
+    tag_mask: [u8; BLOCK_SIZE],
+    add_length: u64,
+    length: u64,
+    // the additional data is done once the data starts.
+    in_data: bool,
+}
+
+impl<B: Block> Drop for GCMStream<'_, B> {
+    fn drop(&mut self) {
+        self.tag_mask.fill(0);
+    }
+}
+
+impl<'a, B: Block> GCMStream<'a, B> {
+    fn new<const N: usize, const T: usize>(gcm: &'a GCM<B, N, T>, nonce: &[u8]) -> Result<Self> {
+        if nonce.len() != N {
+            return Err(Error::InvalidNonceSize(N, nonce.len()));
+        }
+        let (tag_mask, ctr) = start(&gcm.cipher, &gcm.key, nonce);
+        Ok(GCMStream {
+            ctr,
+            ghash: GHash::new(&gcm.key),
+            tag_mask,
+            add_length: 0,
+            length: 0,
+            in_data: false,
+        })
+    }
+
+    fn update_add(&mut self, add: &[u8]) -> Result<()> {
+        if self.in_data {
+            return Err(Error::GCMAdditionalDataAfterData);
+        }
+        self.ghash.update(add);
+        self.add_length += add.len() as u64;
+        Ok(())
+    }
+
+    fn start_data(&mut self, length: usize) -> Result<()> {
+        if !self.in_data {
+            self.ghash.pad();
+            self.in_data = true;
+        }
+        if self.length + length as u64 > MAX_DATA_SIZE {
+            return Err(Error::InvalidInputSize);
+        }
+        self.length += length as u64;
+        Ok(())
+    }
+
+    fn tag(self) -> [u8; BLOCK_SIZE] {
+        let mut ghash = self.ghash.clone();
+        ghash.pad();
+        ghash.update(&lengths_block(self.add_length * 8, self.length * 8));
+        let mut tag = ghash.finalize();
+        tag.iter_mut()
+            .zip(&self.tag_mask)
+            .for_each(|(z, x)| *z ^= *x);
+        tag
+    }
+}
+
+// GCMEncryptor encrypts the data chunk by chunk, the additional data is
+// written before the data, and the tag is returned by finish.
+pub struct GCMEncryptor<'a, B: Block, const T: usize> {
+    s: GCMStream<'a, B>,
+}
+
+impl<'a, B: Block, const T: usize> GCMEncryptor<'a, B, T> {
+    pub fn update_add(&mut self, add: &[u8]) -> Result<()> {
+        self.s.update_add(add)
+    }
+
+    // Encrypts src into dst[..src.len()].
+    pub fn update(&mut self, dst: &mut [u8], src: &[u8]) -> Result<()> {
+        if dst.len() < src.len() {
+            return Err(Error::OutputTooSmall(src.len(), dst.len()));
+        }
+        self.s.start_data(src.len())?;
+        let dst = &mut dst[..src.len()];
+        self.s.ctr.xor_key_stream(dst, src)?;
+        self.s.ghash.update(dst);
+        Ok(())
+    }
+
+    pub fn update_inplace(&mut self, in_out: &mut [u8]) -> Result<()> {
+        self.s.start_data(in_out.len())?;
+        self.s.ctr.xor_key_stream_inplace(in_out);
+        self.s.ghash.update(in_out);
+        Ok(())
+    }
+
+    pub fn finish(self) -> [u8; T] {
+        self.s.tag()[..T].try_into().unwrap()
+    }
+}
+
+// GCMDecryptor decrypts the ciphertext chunk by chunk. The plaintext of
+// update is not authentic until finish succeeds, the caller must not use it
+// before. update_buffered holds the plaintext back, finish releases it only
+// if the tag is valid.
+pub struct GCMDecryptor<'a, B: Block, const T: usize> {
+    s: GCMStream<'a, B>,
+    buffer: Vec<u8>,
+}
+
+impl<'a, B: Block, const T: usize> GCMDecryptor<'a, B, T> {
+    pub fn update_add(&mut self, add: &[u8]) -> Result<()> {
+        self.s.update_add(add)
+    }
+
+    // Decrypts src into dst[..src.len()], the plaintext is unverified.
+    pub fn update(&mut self, dst: &mut [u8], src: &[u8]) -> Result<()> {
+        if dst.len() < src.len() {
+            return Err(Error::OutputTooSmall(src.len(), dst.len()));
+        }
+        self.s.start_data(src.len())?;
+        self.s.ghash.update(src);
+        self.s.ctr.xor_key_stream(&mut dst[..src.len()], src)
+    }
+
+    pub fn update_inplace(&mut self, in_out: &mut [u8]) -> Result<()> {
+        self.s.start_data(in_out.len())?;
+        self.s.ghash.update(in_out);
+        self.s.ctr.xor_key_stream_inplace(in_out);
+        Ok(())
+    }
+
+    // Decrypts src into the buffer returned by finish.
+    pub fn update_buffered(&mut self, src: &[u8]) -> Result<()> {
+        self.s.start_data(src.len())?;
+        self.s.ghash.update(src);
+        let start = self.buffer.len();
+        self.buffer.extend_from_slice(src);
+        self.s.ctr.xor_key_stream_inplace(&mut self.buffer[start..]);
+        Ok(())
+    }
+
+    // Verifies the tag in constant time, and returns the plaintext of
+    // update_buffered, empty if it is not used.
+    pub fn finish(self, tag: &[u8]) -> Result<Vec<u8>> {
+        let mut buffer = self.buffer;
+        let expected_tag = self.s.tag();
+        if tag.len() != T || expected_tag[..T].ct_eq(tag).unwrap_u8() != 1 {
+            buffer.fill(0);
+            return Err(Error::GCMAuthenticationError);
+        }
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use std::vec::Vec;
+
+    const KEY: [u8; 16] = hex!("11754cd72aec309bf52f7687212e8957");
+    const NONCE: [u8; 12] = hex!("3c819d9a9bed087615030b65");
+
+    #[test]
+    fn test_gcm_stream() {
+        let g = new_sm4_gcm_std(&KEY);
+        let add: Vec<u8> = (0..50).collect();
+        let plain: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let mut sealed = vec![0; plain.len() + 16];
+        g.seal(&mut sealed, &NONCE, &plain, Some(&add)).unwrap();
+        let (ciphertext, tag) = sealed.split_at(plain.len());
+
+        for chunk in [1, 15, 16, 17, 100, 256, 1000] {
+            let mut enc = g.encryptor(&NONCE).unwrap();
+            add.chunks(7).for_each(|a| enc.update_add(a).unwrap());
+            let mut out = vec![0; plain.len()];
+            for (dst, src) in out.chunks_mut(chunk).zip(plain.chunks(chunk)) {
+                enc.update(dst, src).unwrap();
+            }
+            assert_eq!(out, ciphertext, "{}", chunk);
+            assert_eq!(enc.finish(), tag, "{}", chunk);
+
+            let mut dec = g.decryptor(&NONCE).unwrap();
+            dec.update_add(&add).unwrap();
+            let mut out = ciphertext.to_vec();
+            out.chunks_mut(chunk).for_each(|c| dec.update_inplace(c).unwrap());
+            assert_eq!(out, plain);
+            assert_eq!(dec.finish(tag).unwrap(), []);
+
+            let mut dec = g.decryptor(&NONCE).unwrap();
+            dec.update_add(&add).unwrap();
+            ciphertext.chunks(chunk).for_each(|c| dec.update_buffered(c).unwrap());
+            assert_eq!(dec.finish(tag).unwrap(), plain);
+        }
+
+        // no additional data, no plaintext.
+        let mut tag = [0; 16];
+        g.seal_inplace(&mut [], &mut tag, &NONCE, None).unwrap();
+        assert_eq!(g.encryptor(&NONCE).unwrap().finish(), tag);
+
+        // the nonce of other length.
+        let g = GCM::<_, 10, 16>::new(sm4::Cipher::new(&KEY));
+        let mut enc = g.encryptor(&NONCE[..10]).unwrap();
+        let mut out = *b"plaintext";
+        enc.update_inplace(&mut out[..4]).unwrap();
+        enc.update_inplace(&mut out[4..]).unwrap();
+        let mut sealed = out.to_vec();
+        sealed.extend_from_slice(&enc.finish());
+        assert_eq!(sealed, hex!("7705c6569e9ada5811d8b7523617ca62ce1aa4a924de38a31d"));
+    }
+
+    #[test]
+    fn test_gcm_stream_errors() {
+        let g = new_sm4_gcm_std(&KEY);
+        let mut enc = g.encryptor(&NONCE).unwrap();
+        let mut data = *b"plaintext";
+        enc.update_inplace(&mut data).unwrap();
+        assert!(matches!(enc.update_add(b"late"), Err(Error::GCMAdditionalDataAfterData)));
+        assert!(matches!(enc.update(&mut [0; 3], b"four"), Err(Error::OutputTooSmall(4, 3))));
+        let tag = enc.finish();
+
+        let mut dec = g.decryptor(&NONCE).unwrap();
+        dec.update_buffered(&data).unwrap();
+        let mut forged = tag;
+        forged[0] ^= 1;
+        assert!(matches!(dec.finish(&forged), Err(Error::GCMAuthenticationError)));
+
+        let mut dec = g.decryptor(&NONCE).unwrap();
+        dec.update_buffered(&data).unwrap();
+        assert!(matches!(dec.finish(&tag[..12]), Err(Error::GCMAuthenticationError)));
+
+        let mut dec = g.decryptor(&NONCE).unwrap();
+        dec.update_buffered(&data).unwrap();
+        assert_eq!(dec.finish(&tag).unwrap(), b"plaintext");
+
+        assert!(matches!(g.encryptor(&NONCE[..8]), Err(Error::InvalidNonceSize(12, 8))));
+    }
+}
@@ -0,0 +1,355 @@
+use subtle::ConstantTimeEq;
+
+use super::gcm::GHash;
+use super::{Error, Result};
+use crate::sm4;
+use crate::traits::AEAD;
+
+const BLOCK_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+// RFC 8452: the plaintext and the additional data are at most 2^36 bytes.
+const MAX_SIZE: u64 = 1 << 36;
+const BATCH_BLOCKS: usize = 16;
+
+// Returns a GCM-SIV instance, the key must have length 16.
+pub fn new_sm4_gcm_siv(key: &[u8]) -> Sm4GcmSiv {
+    Sm4GcmSiv::new(sm4::Cipher::new(key))
+}
+
+// Sm4GcmSiv is the AES-128-GCM-SIV of RFC 8452 with SM4 as the block cipher.
+// A repeated nonce only reveals whether the messages are equal, the keys are
+// derived from the key-generating key for each nonce.
+pub struct Sm4GcmSiv {
+    cipher: sm4::Cipher,
+}
+
+impl Sm4GcmSiv {
+    pub fn new(key_generating_key: sm4::Cipher) -> Self {
+        Sm4GcmSiv { cipher: key_generating_key }
+    }
+
+    // Returns the message authentication key and the message encryption cipher.
+    fn derive_keys(&self, nonce: &[u8]) -> ([u8; BLOCK_SIZE], sm4::Cipher) {
+        let mut blocks = [0u8; 4 * BLOCK_SIZE];
+        for (i, b) in blocks.chunks_exact_mut(BLOCK_SIZE).enumerate() {
+            b[..4].copy_from_slice(&(i as u32).to_le_bytes());
+            b[4..].copy_from_slice(nonce);
+        }
+        self.cipher.encrypt_inplace(&mut blocks);
+
+        // the first half of each block.
+        let mut auth_key = [0u8; BLOCK_SIZE];
+        let mut enc_key = [0u8; BLOCK_SIZE];
+        auth_key[..8].copy_from_slice(&blocks[..8]);
+        auth_key[8..].copy_from_slice(&blocks[16..24]);
+        enc_key[..8].copy_from_slice(&blocks[32..40]);
+        enc_key[8..].copy_from_slice(&blocks[48..56]);
+        let enc = sm4::Cipher::new(&enc_key);
+        enc_key.fill(0);
+        blocks.fill(0);
+        (auth_key, enc)
+    }
+
+    fn tag(
+        &self,
+        auth_key: &[u8; BLOCK_SIZE],
+        enc: &sm4::Cipher,
+        nonce: &[u8],
+        plaintext: &[u8],
+        add: &[u8],
+    ) -> [u8; BLOCK_SIZE] {
+        let mut p = Polyval::new(auth_key);
+        p.update(add);
+        p.pad();
+        p.update(plaintext);
+        p.pad();
+        let mut lengths = [0u8; BLOCK_SIZE];
+        lengths[..8].copy_from_slice(&(add.len() as u64 * 8).to_le_bytes());
+        lengths[8..].copy_from_slice(&(plaintext.len() as u64 * 8).to_le_bytes());
+        p.update(&lengths);
+
+        let mut s = p.finalize();
+        s.iter_mut().zip(nonce).for_each(|(s, n)| *s ^= *n);
+        s[BLOCK_SIZE - 1] &= 0x7f;
+        enc.encrypt_inplace(&mut s);
+        s
+    }
+
+    fn check(&self, nonce: &[u8], length: usize, add: &[u8]) -> Result<()> {
+        if nonce.len() != NONCE_SIZE {
+            return Err(Error::InvalidNonceSize(NONCE_SIZE, nonce.len()));
+        }
+        if length as u64 > MAX_SIZE || add.len() as u64 > MAX_SIZE {
+            return Err(Error::InvalidInputSize);
+        }
+        Ok(())
+    }
+}
+
+// The counter is the first 4 bytes of the tag in little-endian, with the
+// most significant bit of the last byte set.
+fn ctr(enc: &sm4::Cipher, tag: &[u8; BLOCK_SIZE], in_out: &mut [u8]) {
+    let mut counter_block = *tag;
+    counter_block[BLOCK_SIZE - 1] |= 0x80;
+    let mut counter = u32::from_le_bytes(counter_block[..4].try_into().unwrap());
+
+    let mut keystream = [0u8; BATCH_BLOCKS * BLOCK_SIZE];
+    for chunk in in_out.chunks_mut(keystream.len()) {
+        let ks = &mut keystream[..chunk.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE];
+        for b in ks.chunks_exact_mut(BLOCK_SIZE) {
+            b.copy_from_slice(&counter_block);
+            b[..4].copy_from_slice(&counter.to_le_bytes());
+            counter = counter.wrapping_add(1);
+        }
+        enc.encrypt_inplace(ks);
+        chunk.iter_mut().zip(ks.iter()).for_each(|(z, k)| *z ^= *k);
+    }
+    keystream.fill(0);
+}
+
+impl AEAD for Sm4GcmSiv {
+    type Error = super::Error;
+
+    fn overhead(&self) -> usize {
+        TAG_SIZE
+    }
+
+    fn nonce_size(&self) -> usize {
+        NONCE_SIZE
+    }
+
+    fn seal_inplace(
+        &self,
+        in_out: &mut [u8],
+        tag: &mut [u8],
+        nonce: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<()> {
+        let add = add.unwrap_or(&[]);
+        self.check(nonce, in_out.len(), add)?;
+        if tag.len() < TAG_SIZE {
+            return Err(Error::OutputTooSmall(TAG_SIZE, tag.len()));
+        }
+
+        let (mut auth_key, enc) = self.derive_keys(nonce);
+        let t = self.tag(&auth_key, &enc, nonce, in_out, add);
+        auth_key.fill(0);
+        ctr(&enc, &t, in_out);
+        tag[..TAG_SIZE].copy_from_slice(&t);
+        Ok(())
+    }
+
+    fn open_inplace(
+        &self,
+        in_out: &mut [u8],
+        tag: &[u8],
+        nonce: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<()> {
+        let add = add.unwrap_or(&[]);
+        if nonce.len() != NONCE_SIZE || tag.len() != TAG_SIZE {
+            return Err(Error::GCMAuthenticationError);
+        }
+        self.check(nonce, in_out.len(), add)?;
+
+        let (mut auth_key, enc) = self.derive_keys(nonce);
+        ctr(&enc, tag.try_into().unwrap(), in_out);
+        let expected_tag = self.tag(&auth_key, &enc, nonce, in_out, add);
+        auth_key.fill(0);
+        if expected_tag.ct_eq(tag).unwrap_u8() != 1 {
+            in_out.fill(0);
+            return Err(Error::GCMAuthenticationError);
+        }
+        Ok(())
+    }
+
+    fn seal(
+        &self,
+        out: &mut [u8],
+        nonce: &[u8],
+        plaintext: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<()> {
+        let plaintext_length = plaintext.len();
+        if out.len() < plaintext_length + TAG_SIZE {
+            return Err(Error::OutputTooSmall(plaintext_length + TAG_SIZE, out.len()));
+        }
+
+        let (ciphertext, tag) = out.split_at_mut(plaintext_length);
+        ciphertext.copy_from_slice(plaintext);
+        self.seal_inplace(ciphertext, &mut tag[..TAG_SIZE], nonce, add)
+    }
+
+    fn open(
+        &self,
+        out: &mut [u8],
+        nonce: &[u8],
+        ciphertext: &[u8],
+        add: Option<&[u8]>,
+    ) -> Result<usize> {
+        if ciphertext.len() < TAG_SIZE {
+            return Err(Error::GCMCiphertextTooSmall(ciphertext.len(), TAG_SIZE));
+        }
+
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - TAG_SIZE);
+        if out.len() < ciphertext.len() {
+            return Err(Error::OutputBufferTooShort(
+                ciphertext.len(),
+                out.len(),
+            ));
+        }
+        let out = &mut out[..ciphertext.len()];
+        out.copy_from_slice(ciphertext);
+        self.open_inplace(out, tag, nonce, add)?;
+        Ok(ciphertext.len())
+    }
+}
+
+// Polyval is the POLYVAL of RFC 8452 on top of GHASH, see its Appendix A:
+// POLYVAL(H, X_1, ..., X_n) =
+//     ByteReverse(GHASH(mulX_GHASH(ByteReverse(H)), ByteReverse(X_1), ..., ByteReverse(X_n)))
+pub struct Polyval {
+    ghash: GHash,
+    buf: [u8; BLOCK_SIZE],
+    pos: usize,
+}
+
+impl Polyval {
+    pub fn new(key: &[u8; BLOCK_SIZE]) -> Self {
+        let mut h = *key;
+        h.reverse();
+        // mulX_GHASH: the GHASH field is bit reflected, a right shift is a
+        // multiplication by x, reduced by R = 0xe1 || 0^120.
+        let v = u128::from_be_bytes(h);
+        let v = (v >> 1) ^ ((v & 1).wrapping_neg() & (0xe1 << 120));
+        h = v.to_be_bytes();
+        let ghash = GHash::new(&h);
+        h.fill(0);
+        Polyval { ghash, buf: [0; BLOCK_SIZE], pos: 0 }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        if self.pos > 0 {
+            let n = (BLOCK_SIZE - self.pos).min(data.len());
+            self.buf[self.pos..self.pos + n].copy_from_slice(&data[..n]);
+            self.pos += n;
+            data = &data[n..];
+            if self.pos < BLOCK_SIZE {
+                return;
+            }
+            self.buf.reverse();
+            self.ghash.update(&self.buf);
+            self.pos = 0;
+        }
+
+        // reversed in batches, so that GHASH takes its widest path.
+        let mut reversed = [0u8; BATCH_BLOCKS * BLOCK_SIZE];
+        let full_blocks = data.len() - data.len() % BLOCK_SIZE;
+        for chunk in data[..full_blocks].chunks(reversed.len()) {
+            let r = &mut reversed[..chunk.len()];
+            for (dst, src) in r.chunks_exact_mut(BLOCK_SIZE).zip(chunk.chunks_exact(BLOCK_SIZE)) {
+                dst.iter_mut().zip(src.iter().rev()).for_each(|(d, s)| *d = *s);
+            }
+            self.ghash.update(r);
+        }
+
+        let rest = &data[full_blocks..];
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.pos = rest.len();
+    }
+
+    // Pads the partial block with zeros, nothing if the data is aligned.
+    pub fn pad(&mut self) {
+        if self.pos > 0 {
+            self.buf[self.pos..].fill(0);
+            self.buf.reverse();
+            self.ghash.update(&self.buf);
+            self.pos = 0;
+        }
+    }
+
+    // Pads the data and returns the hash.
+    pub fn finalize(mut self) -> [u8; BLOCK_SIZE] {
+        self.pad();
+        let mut s = self.ghash.finalize();
+        s.reverse();
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_polyval() {
+        // RFC 8452, Appendix A
+        let h = hex!("25629347589242761d31f826ba4b757b");
+        let x = hex!("4f4f95668c83dfb6401762bb2d01a262d1a24ddd2721d006bbe45f20d3c9f362");
+        let mut p = Polyval::new(&h);
+        p.update(&x);
+        assert_eq!(p.finalize(), hex!("f7a3b47b846119fae5b7866cf5e5b77e"));
+
+        let mut p = Polyval::new(&h);
+        x.iter().for_each(|b| p.update(&[*b]));
+        assert_eq!(p.finalize(), hex!("f7a3b47b846119fae5b7866cf5e5b77e"));
+    }
+
+    #[test]
+    fn test_gcm_siv() {
+        // printed by scripts/sm4_gcm_siv_vectors.py.
+        let key = hex!("0123456789ABCDEFFEDCBA9876543210");
+        let nonce = hex!("00001234567800000000ABCD");
+        let msg = b"AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDDEEEEEE";
+        let add = hex!("FEEDFACEDEADBEEFFEEDFACEDEADBEEFABADDAD2");
+        let tests: [(usize, usize, &[u8]); 5] = [
+            (0, 0, &hex!("00165c53d227ab36a50832027ee312ed")),
+            (0, 20, &hex!("5aaaffb0ad2d2566785a6ff155f11731")),
+            (9, 0, &hex!("523f78625b515180decac9edf68cee20b7982a07178be72a71")),
+            (32, 20, &hex!("cfa83f0f71f0ca85529ccff7819c5427ebc7050b582ee2b985da019a608a81290d8697f0c7cf0622c7e4bdcbb5e58962")),
+            (70, 20, &hex!("eb1eca6937da563c1055e73b8b349ab438c597eaff34bbfbea33a7ca03deffcfa8836e66de4f2fd32cf67ef9b6c4769a35b55eab99f0e24ed5b7a3936a094f3b92cf89314a452b1641e6d2542dd8aef0927ec85cec3a")),
+        ];
+        let g = new_sm4_gcm_siv(&key);
+        for (n, a, wanted) in tests {
+            let add = if a == 0 { None } else { Some(&add[..a]) };
+            let mut out = vec![0; n + g.overhead()];
+            g.seal(&mut out, &nonce, &msg[..n], add).unwrap();
+            assert_eq!(out, wanted, "{} {}", n, a);
+
+            let mut decrypted = vec![0; n];
+            assert_eq!(g.open(&mut decrypted, &nonce, &out, add).unwrap(), n);
+            assert_eq!(decrypted, msg[..n]);
+        }
+    }
+
+    #[test]
+    fn test_gcm_siv_errors() {
+        let g = new_sm4_gcm_siv(&hex!("0123456789ABCDEFFEDCBA9876543210"));
+        let nonce = [7u8; 12];
+        let plain: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        let mut sealed = vec![0; plain.len() + 16];
+        g.seal(&mut sealed, &nonce, &plain, Some(b"add")).unwrap();
+
+        // deterministic, and another nonce gives another ciphertext.
+        let mut again = vec![0; plain.len() + 16];
+        g.seal(&mut again, &nonce, &plain, Some(b"add")).unwrap();
+        assert_eq!(sealed, again);
+        g.seal(&mut again, &[8u8; 12], &plain, Some(b"add")).unwrap();
+        assert_ne!(sealed, again);
+
+        let mut out = vec![0xff; plain.len()];
+        for i in [0, 299, 300, 315] {
+            let mut forged = sealed.clone();
+            forged[i] ^= 1;
+            assert!(matches!(g.open(&mut out, &nonce, &forged, Some(b"add")), Err(Error::GCMAuthenticationError)));
+            assert!(out.iter().all(|b| *b == 0));
+        }
+        assert!(matches!(g.open(&mut out, &nonce, &sealed, None), Err(Error::GCMAuthenticationError)));
+        assert!(matches!(g.open(&mut out, &nonce[..8], &sealed, Some(b"add")), Err(Error::GCMAuthenticationError)));
+        assert!(matches!(g.seal(&mut out, &nonce[..8], &plain[..8], None), Err(Error::InvalidNonceSize(12, 8))));
+        assert!(matches!(g.open(&mut out, &nonce, &sealed[..15], None), Err(Error::GCMCiphertextTooSmall(15, 16))));
+    }
+}
@@ -1,5 +1,13 @@
 pub mod cbc;
+pub mod ccm;
+pub mod cfb;
+pub mod ctr;
+pub mod ecb;
 pub mod gcm;
+pub mod gcm_siv;
+pub mod ofb;
+pub mod padding;
+pub mod xts;
 use thiserror;
 
 #[derive(thiserror::Error, Debug)]
@@ -7,15 +15,36 @@ pub enum Error {
     #[error("invalid input size")]
     InvalidInputSize,
 
+    #[error("invalid padding")]
+    InvalidPadding,
+
     #[error("invalid nonce size, want {}, got {}", .0, .1)]
     InvalidNonceSize(usize, usize),
 
+    #[error("invalid key size, want {}, got {}", .0, .1)]
+    InvalidKeySize(usize, usize),
+
+    #[error("invalid tag size {}", .0)]
+    InvalidTagSize(usize),
+
+    #[error("invalid counter width {}, want 1..=128", .0)]
+    InvalidCounterWidth(u32),
+
     #[error("GCM authentication failed while decrypting")]
     GCMAuthenticationError,
     
+    #[error("GCM additional data must be written before the data")]
+    GCMAdditionalDataAfterData,
+
     #[error("GCM ciphertext's length ({}) is shorter than tag size({})", .0, .1)]
     GCMCiphertextTooSmall(usize, usize),
 
+    #[error("CCM authentication failed while decrypting")]
+    CCMAuthenticationError,
+
+    #[error("CCM ciphertext's length ({}) is shorter than tag size({})", .0, .1)]
+    CCMCiphertextTooSmall(usize, usize),
+
     #[error("output buffer's length too short, want {}, got {}", .0, .1)]
     OutputBufferTooShort(usize, usize),
 
@@ -0,0 +1,86 @@
+use super::{Error, Result};
+use crate::traits::Block;
+
+const BLOCK_SIZE: usize = 16;
+
+// OFBMode is a stream cipher, encryption and decryption are the same.
+pub struct OFBMode<B: Block> {
+    pub block: B,
+    // the last output block, out[pos..] is not used yet.
+    out: [u8; BLOCK_SIZE],
+    pos: usize,
+}
+
+impl<B: Block> OFBMode<B> {
+    pub fn new(block: B, iv: &[u8]) -> Result<Self> {
+        debug_assert_eq!(block.block_size(), BLOCK_SIZE);
+        if iv.len() != BLOCK_SIZE {
+            return Err(Error::InvalidNonceSize(BLOCK_SIZE, iv.len()));
+        }
+        Ok(OFBMode {
+            block,
+            out: iv.try_into().unwrap(),
+            pos: BLOCK_SIZE,
+        })
+    }
+
+    pub fn xor_key_stream(&mut self, dst: &mut [u8], src: &[u8]) -> Result<()> {
+        if dst.len() < src.len() {
+            return Err(Error::OutputTooSmall(src.len(), dst.len()));
+        }
+        let dst = &mut dst[..src.len()];
+        dst.copy_from_slice(src);
+        self.xor_key_stream_inplace(dst);
+        Ok(())
+    }
+
+    pub fn xor_key_stream_inplace(&mut self, in_out: &mut [u8]) {
+        for x in in_out {
+            if self.pos == BLOCK_SIZE {
+                self.block.encrypt_inplace(&mut self.out);
+                self.pos = 0;
+            }
+            *x ^= self.out[self.pos];
+            self.pos += 1;
+        }
+    }
+}
+
+impl<B: Block> Drop for OFBMode<B> {
+    fn drop(&mut self) {
+        self.out.fill(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sm4::Cipher;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_ofb() {
+        // openssl enc -sm4-ofb
+        let key = hex!("0123456789ABCDEFFEDCBA9876543210");
+        let iv = hex!("000102030405060708090A0B0C0D0E0F");
+        let plain = hex!("AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDDEEEEEEEEEEEEEEEEFFFFFFFFFFFFFFFFAAAAAAAAAAAAAAAABBBBBBBBBBBBBBBB");
+        let wanted = hex!("ac3236cb970cc20791364c395a1342d13f238e807b4f96b1bc82314900fe35fdb5a976a661e7e9c6cf11fbd9db4fa11d9db8e26fd243c191404fb13179854094");
+
+        let mut out = [0; 64];
+        let mut ofb = OFBMode::new(Cipher::new(&key), &iv).unwrap();
+        ofb.xor_key_stream(&mut out, &plain).unwrap();
+        assert_eq!(out, wanted);
+
+        for split in [1, 15, 16, 20, 63] {
+            let mut data = wanted;
+            let mut ofb = OFBMode::new(Cipher::new(&key), &iv).unwrap();
+            let (a, b) = data.split_at_mut(split);
+            ofb.xor_key_stream_inplace(a);
+            ofb.xor_key_stream_inplace(b);
+            assert_eq!(data, plain);
+        }
+
+        let mut ofb = OFBMode::new(Cipher::new(&key), &iv).unwrap();
+        assert!(matches!(ofb.xor_key_stream(&mut out[..10], &plain), Err(Error::OutputTooSmall(64, 10))));
+    }
+}
@@ -0,0 +1,169 @@
+use alloc::vec::Vec;
+
+use super::{Error, Result};
+
+// Padding fills the data up to a multiple of the block size.
+// Except Zero, a full block is added if the data is already aligned, so
+// that the padding is always removable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Padding {
+    // n bytes of value n, RFC 5652.
+    PKCS7,
+    // 0x80 then zeros, ISO/IEC 7816-4 and ISO/IEC 9797-1 method 2.
+    ISO7816,
+    // zeros then the byte n, ANSI X9.23.
+    ANSIX923,
+    // zeros, nothing if the data is aligned. The trailing zeros of the data
+    // are removed by unpad as well.
+    Zero,
+}
+
+impl Padding {
+    pub fn pad(&self, data: &mut Vec<u8>, block_size: usize) {
+        assert!(block_size > 0 && block_size < 256);
+        let n = block_size - data.len() % block_size;
+        let length = data.len() + n;
+        match self {
+            Padding::PKCS7 => data.resize(length, n as u8),
+            Padding::ISO7816 => {
+                data.push(0x80);
+                data.resize(length, 0);
+            }
+            Padding::ANSIX923 => {
+                data.resize(length - 1, 0);
+                data.push(n as u8);
+            }
+            Padding::Zero => {
+                if n != block_size {
+                    data.resize(length, 0);
+                }
+            }
+        }
+    }
+
+    // Returns the length of the unpadded data. The whole last block is read
+    // without branching on the byte values, so that a decryption oracle
+    // can not learn the padding from the timing.
+    pub fn unpad(&self, data: &[u8], block_size: usize) -> Result<usize> {
+        assert!(block_size > 0 && block_size < 256);
+        if data.len() % block_size != 0 {
+            return Err(Error::InvalidInputSize);
+        }
+        if data.is_empty() {
+            return match self {
+                Padding::Zero => Ok(0),
+                _ => Err(Error::InvalidPadding),
+            };
+        }
+
+        let last = &data[data.len() - block_size..];
+        let (n, bad) = match self {
+            Padding::PKCS7 | Padding::ANSIX923 => {
+                let n = last[block_size - 1];
+                let mut bad = (n == 0) as u8 | (n as usize > block_size) as u8;
+                // the byte n itself is at i = 0.
+                for i in 1..block_size {
+                    let in_padding = ((i as u8) < n) as u8;
+                    let b = last[block_size - 1 - i];
+                    let want = if *self == Padding::PKCS7 { n } else { 0 };
+                    bad |= in_padding & (b != want) as u8;
+                }
+                (n as usize, bad)
+            }
+            Padding::ISO7816 | Padding::Zero => {
+                // n is the length of the trailing zeros plus the first non-zero byte.
+                let mut n = 0usize;
+                let mut found = 0u8;
+                let mut bad = 0u8;
+                for i in 0..block_size {
+                    let b = last[block_size - 1 - i];
+                    let first = (b != 0) as u8 & !found & 1;
+                    bad |= first & (b != 0x80) as u8;
+                    n |= (i + 1) & (first as usize).wrapping_neg();
+                    found |= first;
+                }
+                if *self == Padding::Zero {
+                    // the non-zero byte is data.
+                    let all_zero = (found ^ 1) as usize;
+                    n = (n.wrapping_sub(1) & all_zero.wrapping_sub(1)) | (block_size & all_zero.wrapping_neg());
+                    (n, 0)
+                } else {
+                    (n, bad | (found ^ 1))
+                }
+            }
+        };
+        if bad != 0 {
+            return Err(Error::InvalidPadding);
+        }
+        Ok(data.len() - n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_pad() {
+        let tests: [(Padding, usize, &[u8], &[u8]); 8] = [
+            (Padding::PKCS7, 7, &hex!("0102030405"), &hex!("01020304050202")),
+            (Padding::PKCS7, 4, &hex!("01020304"), &hex!("0102030404040404")),
+            (Padding::ISO7816, 7, &hex!("0102030405"), &hex!("01020304058000")),
+            (Padding::ISO7816, 4, &hex!("01020304"), &hex!("0102030480000000")),
+            (Padding::ANSIX923, 7, &hex!("0102030405"), &hex!("01020304050002")),
+            (Padding::ANSIX923, 4, &hex!(""), &hex!("00000004")),
+            (Padding::Zero, 7, &hex!("0102030405"), &hex!("01020304050000")),
+            (Padding::Zero, 4, &hex!("01020304"), &hex!("01020304")),
+        ];
+        for (padding, block_size, data, wanted) in tests {
+            let mut padded = data.to_vec();
+            padding.pad(&mut padded, block_size);
+            assert_eq!(padded, wanted, "{:?}", padding);
+            let n = padding.unpad(&padded, block_size).unwrap();
+            assert_eq!(&padded[..n], data, "{:?}", padding);
+        }
+    }
+
+    #[test]
+    fn test_unpad() {
+        let mut data = [3u8; 16];
+        assert_eq!(Padding::PKCS7.unpad(&data, 16).unwrap(), 13);
+        data[13] = 2;
+        assert!(matches!(Padding::PKCS7.unpad(&data, 16), Err(Error::InvalidPadding)));
+        assert!(matches!(Padding::PKCS7.unpad(&[0u8; 16], 16), Err(Error::InvalidPadding)));
+        assert!(matches!(Padding::PKCS7.unpad(&[17u8; 16], 16), Err(Error::InvalidPadding)));
+        assert_eq!(Padding::PKCS7.unpad(&[16u8; 16], 16).unwrap(), 0);
+        assert!(matches!(Padding::PKCS7.unpad(&[], 16), Err(Error::InvalidPadding)));
+        assert!(matches!(Padding::PKCS7.unpad(&[1u8; 15], 16), Err(Error::InvalidInputSize)));
+
+        assert!(matches!(Padding::ANSIX923.unpad(&hex!("00000103"), 4), Err(Error::InvalidPadding)));
+        assert_eq!(Padding::ANSIX923.unpad(&hex!("0a000003"), 4).unwrap(), 1);
+
+        assert_eq!(Padding::ISO7816.unpad(&hex!("0a0b0c80"), 4).unwrap(), 3);
+        assert_eq!(Padding::ISO7816.unpad(&hex!("80000000"), 4).unwrap(), 0);
+        assert!(matches!(Padding::ISO7816.unpad(&hex!("00000000"), 4), Err(Error::InvalidPadding)));
+        assert!(matches!(Padding::ISO7816.unpad(&hex!("0a0b0c81"), 4), Err(Error::InvalidPadding)));
+        assert!(matches!(Padding::ISO7816.unpad(&hex!("0a800100"), 4), Err(Error::InvalidPadding)));
+
+        // only the zeros of the last block are removed.
+        assert_eq!(Padding::Zero.unpad(&hex!("0a00000000000000"), 4).unwrap(), 4);
+        assert_eq!(Padding::Zero.unpad(&hex!("0a0b0c0d0e000000"), 4).unwrap(), 5);
+        assert_eq!(Padding::Zero.unpad(&hex!("0a0b0c0d"), 4).unwrap(), 4);
+        assert_eq!(Padding::Zero.unpad(&[], 4).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_pad_lengths() {
+        for padding in [Padding::PKCS7, Padding::ISO7816, Padding::ANSIX923] {
+            for len in 0..=33 {
+                let data: Vec<u8> = (1..=len as u8).collect();
+                let mut padded = data.clone();
+                padding.pad(&mut padded, 16);
+                assert_eq!(padded.len(), (len / 16 + 1) * 16);
+                assert_eq!(padding.unpad(&padded, 16).unwrap(), len);
+            }
+        }
+    }
+}
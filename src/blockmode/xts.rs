@@ -0,0 +1,210 @@
+use super::{Error, Result};
+use crate::sm4;
+use crate::traits::Block;
+
+const BLOCK_SIZE: usize = 16;
+
+// The tweaked blocks are crypted 16 at a time, so that the block cipher
+// takes its widest path, e.g., sm4::Blocks' block16.
+const BATCH_SIZE: usize = 16 * BLOCK_SIZE;
+
+// Returns an XTS instance of SM4, the key is key1 || key2 of 32 bytes.
+pub fn new_sm4_xts(key: &[u8]) -> Result<XTSMode<sm4::Cipher>> {
+    if key.len() != 2 * sm4::KEY_SIZE {
+        return Err(Error::InvalidKeySize(2 * sm4::KEY_SIZE, key.len()));
+    }
+    let (key1, key2) = key.split_at(sm4::KEY_SIZE);
+    Ok(XTSMode::new(sm4::Cipher::new(key1), sm4::Cipher::new(key2)))
+}
+
+// XTSMode is the XTS of IEEE 1619 for the disk sectors, with ciphertext stealing
+// for the sectors of any length no less than a block. The tweak is the sector
+// number in little-endian, i.e., the plain64 IV of dm-crypt.
+pub struct XTSMode<B: Block> {
+    // key1 crypts the data, key2 encrypts the tweak.
+    pub block1: B,
+    pub block2: B,
+}
+
+impl<B: Block> XTSMode<B> {
+    pub fn new(block1: B, block2: B) -> Self {
+        debug_assert_eq!(block1.block_size(), BLOCK_SIZE);
+        debug_assert_eq!(block2.block_size(), BLOCK_SIZE);
+        XTSMode { block1, block2 }
+    }
+
+    pub fn encrypt_sector(&self, dst: &mut [u8], src: &[u8], sector: u64) -> Result<()> {
+        copy_to(dst, src)?;
+        self.encrypt_sector_inplace(&mut dst[..src.len()], sector)
+    }
+
+    pub fn decrypt_sector(&self, dst: &mut [u8], src: &[u8], sector: u64) -> Result<()> {
+        copy_to(dst, src)?;
+        self.decrypt_sector_inplace(&mut dst[..src.len()], sector)
+    }
+
+    pub fn encrypt_sector_inplace(&self, in_out: &mut [u8], sector: u64) -> Result<()> {
+        self.crypt_inplace(in_out, &sector_tweak(sector), false)
+    }
+
+    pub fn decrypt_sector_inplace(&self, in_out: &mut [u8], sector: u64) -> Result<()> {
+        self.crypt_inplace(in_out, &sector_tweak(sector), true)
+    }
+
+    // The tweak is any 16-byte value, e.g., the essiv of dm-crypt.
+    pub fn encrypt_inplace_with_tweak(&self, in_out: &mut [u8], tweak: &[u8; BLOCK_SIZE]) -> Result<()> {
+        self.crypt_inplace(in_out, tweak, false)
+    }
+
+    pub fn decrypt_inplace_with_tweak(&self, in_out: &mut [u8], tweak: &[u8; BLOCK_SIZE]) -> Result<()> {
+        self.crypt_inplace(in_out, tweak, true)
+    }
+
+    fn crypt_inplace(&self, in_out: &mut [u8], tweak: &[u8; BLOCK_SIZE], decrypt: bool) -> Result<()> {
+        if in_out.len() < BLOCK_SIZE {
+            return Err(Error::InvalidInputSize);
+        }
+        let mut t = *tweak;
+        self.block2.encrypt_inplace(&mut t);
+
+        // the last full block and the partial one are left to the ciphertext stealing.
+        let r = in_out.len() % BLOCK_SIZE;
+        let bulk_len = if r == 0 { in_out.len() } else { in_out.len() - BLOCK_SIZE - r };
+        let (bulk, tail) = in_out.split_at_mut(bulk_len);
+
+        let mut tweaks = [0u8; BATCH_SIZE];
+        for chunk in bulk.chunks_mut(BATCH_SIZE) {
+            let tweaks = &mut tweaks[..chunk.len()];
+            for x in tweaks.chunks_exact_mut(BLOCK_SIZE) {
+                x.copy_from_slice(&t);
+                mul_alpha(&mut t);
+            }
+            xor(chunk, tweaks);
+            self.crypt_blocks(chunk, decrypt);
+            xor(chunk, tweaks);
+        }
+        tweaks.fill(0);
+
+        if r != 0 {
+            let (last, partial) = tail.split_at_mut(BLOCK_SIZE);
+            let t1 = t;
+            mul_alpha(&mut t);
+            // the decryption of the last full block uses the next tweak.
+            let (first, second) = if decrypt { (&t, &t1) } else { (&t1, &t) };
+
+            let mut b: [u8; BLOCK_SIZE] = (*last).try_into().unwrap();
+            self.crypt_block(&mut b, first, decrypt);
+            let mut stolen = [0u8; BLOCK_SIZE];
+            stolen[..r].copy_from_slice(partial);
+            stolen[r..].copy_from_slice(&b[r..]);
+            partial.copy_from_slice(&b[..r]);
+            self.crypt_block(&mut stolen, second, decrypt);
+            last.copy_from_slice(&stolen);
+            b.fill(0);
+            stolen.fill(0);
+        }
+        t.fill(0);
+        Ok(())
+    }
+
+    fn crypt_block(&self, b: &mut [u8; BLOCK_SIZE], t: &[u8; BLOCK_SIZE], decrypt: bool) {
+        xor(b, t);
+        self.crypt_blocks(b, decrypt);
+        xor(b, t);
+    }
+
+    #[inline]
+    fn crypt_blocks(&self, in_out: &mut [u8], decrypt: bool) {
+        if decrypt {
+            self.block1.decrypt_inplace(in_out);
+        } else {
+            self.block1.encrypt_inplace(in_out);
+        }
+    }
+}
+
+fn copy_to(dst: &mut [u8], src: &[u8]) -> Result<()> {
+    if dst.len() < src.len() {
+        return Err(Error::OutputTooSmall(src.len(), dst.len()));
+    }
+    dst[..src.len()].copy_from_slice(src);
+    Ok(())
+}
+
+fn sector_tweak(sector: u64) -> [u8; BLOCK_SIZE] {
+    let mut tweak = [0u8; BLOCK_SIZE];
+    tweak[..8].copy_from_slice(&sector.to_le_bytes());
+    tweak
+}
+
+// Multiplies the tweak by the primitive element α of GF(2^128),
+// x^128 + x^7 + x^2 + x + 1, in the little-endian order of IEEE 1619.
+#[inline]
+fn mul_alpha(t: &mut [u8; BLOCK_SIZE]) {
+    let x = u128::from_le_bytes(*t);
+    let carry = (x >> 127) as u8;
+    let x = (x << 1) ^ (0x87 * carry as u128);
+    *t = x.to_le_bytes();
+}
+
+#[inline]
+fn xor(dst: &mut [u8], src: &[u8]) {
+    dst.iter_mut().zip(src).for_each(|(z, x)| *z ^= *x);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sm3;
+    use std::vec::Vec;
+    use hex_literal::hex;
+
+    const KEY: [u8; 32] = hex!("2B7E151628AED2A6ABF7158809CF4F3C000102030405060708090A0B0C0D0E0F");
+
+    #[test]
+    fn test_xts() {
+        // the IEEE 1619 vector of SM4-XTS in OpenSSL's evpciph_sm4.txt.
+        let tweak = hex!("F0F1F2F3F4F5F6F7F8F9FAFBFCFDFEFF");
+        let plain = hex!("6BC1BEE22E409F96E93D7E117393172AAE2D8A571E03AC9C9EB76FAC45AF8E5130C81C46A35CE411E5FBC1191A0A52EFF69F2445DF4F9B17");
+        let wanted = hex!("E9538251C71D7B80BBE4483FEF497BD1B3DB1A3E60408C575D63FF7DB39F83260869F9E2585FEC9F0B863BF8FD784B8627D16C0DB6D2CFC7");
+
+        let xts = new_sm4_xts(&KEY).unwrap();
+        let mut data = plain;
+        xts.encrypt_inplace_with_tweak(&mut data, &tweak).unwrap();
+        assert_eq!(data, wanted);
+        xts.decrypt_inplace_with_tweak(&mut data, &tweak).unwrap();
+        assert_eq!(data, plain);
+    }
+
+    #[test]
+    fn test_xts_sector() {
+        // a 517-byte sector 0x123456789 crosses a batch and steals 5 bytes,
+        // the ciphertext is printed by scripts/sm4_xts_vectors.py.
+        let plain: Vec<u8> = (0..517).map(|i| (i % 251) as u8).collect();
+        let xts = new_sm4_xts(&KEY).unwrap();
+        let mut data = vec![0; 517];
+        xts.encrypt_sector(&mut data, &plain, 0x123456789).unwrap();
+        assert_eq!(data[..32], hex!("c6fa0e7be8e98fcedf92226687ece923683334a94d666cd05807b74b7325f4de"));
+        assert_eq!(data[496..], hex!("39ac1c6165db23f43ccc5e4ee0e407032925426345"));
+        let mut d = sm3::Digest::new();
+        d.write(&data);
+        assert_eq!(d.sum(), hex!("029137a153a0ebcfbab6a535485de66e0da1e5b4424e130ba9ed949ecccb59c1"));
+
+        let mut out = vec![0; 517];
+        xts.decrypt_sector(&mut out, &data, 0x123456789).unwrap();
+        assert_eq!(out, plain);
+
+        // all the lengths of the stealing, and the batches.
+        for n in [16, 17, 31, 32, 33, 255, 256, 257, 272, 287, 4096] {
+            let plain: Vec<u8> = (0..n).map(|i| (i * 7) as u8).collect();
+            let mut data = plain.clone();
+            xts.encrypt_sector_inplace(&mut data, 7).unwrap();
+            assert_ne!(data, plain);
+            xts.decrypt_sector_inplace(&mut data, 7).unwrap();
+            assert_eq!(data, plain, "{}", n);
+        }
+
+        assert!(matches!(xts.encrypt_sector_inplace(&mut [0; 15], 0), Err(Error::InvalidInputSize)));
+        assert!(matches!(new_sm4_xts(&KEY[..16]), Err(Error::InvalidKeySize(32, 16))));
+    }
+}
@@ -0,0 +1,32 @@
+use thiserror;
+
+use crate::sm2::error::SM2Error;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("sm2 ciphertext too short, want at least {}, got {}", .0, .1)]
+    CiphertextTooShort(usize, usize),
+
+    #[error("unsupported sm2 point format: {:#04x}", .0)]
+    UnsupportedPointFormat(u8),
+
+    #[error("malformed ASN.1 sm2 ciphertext")]
+    InvalidASN1Ciphertext,
+
+    #[error("failed to encode the ASN.1 sm2 ciphertext")]
+    ASN1EncodingFailed,
+
+    #[error("sm2 ciphertext coordinate out of range")]
+    InvalidCoordinate,
+
+    #[error("invalid sm2 ciphertext hash length, want {}, got {}", .0, .1)]
+    InvalidHashLength(usize, usize),
+
+    #[error("{} trailing bytes after sm2 ciphertext", .0)]
+    TrailingData(usize),
+
+    #[error(transparent)]
+    SM2(#[from] SM2Error),
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
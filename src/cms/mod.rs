@@ -1 +1,3 @@
-pub mod sm2;
\ No newline at end of file
+pub mod errors;
+pub mod sm2;
+pub use errors::{Error, Result};
@@ -1,14 +1,26 @@
 use num::BigInt;
+use num::bigint::Sign;
 use crate::{sm2::*, sm3};
 use crate::cryptobyte::{Builder, Parser};
+use crate::sm2::error::SM2Error;
+use super::errors::{Error, Result};
 
 use alloc::vec::*;
 
-fn bigint_to_u256(n: &BigInt) ->U256{
-    let (_, digits) = n.to_u64_digits();
-    U256{
-        v:[digits[0], digits[1], digits[2], digits[3]],
+// Returns None if n is negative or does not fit in 256 bits.
+fn bigint_to_u256(n: &BigInt) -> Option<U256> {
+    let (sign, digits) = n.to_u64_digits();
+    if sign == Sign::Minus || digits.len() > 4 {
+        return None;
     }
+    let mut v = [0u64; 4];
+    v[..digits.len()].copy_from_slice(&digits);
+    Some(U256 { v })
+}
+
+// C1 is a point on the curve, the same check as a public key.
+fn is_valid_c1(x: &U256, y: &U256) -> bool {
+    PublicKey { x: *x, y: *y }.is_valid()
 }
 
 fn u256_to_bigint(n: &U256) ->BigInt{
@@ -23,6 +35,7 @@ const PRE_MASTER_KEY_SIZE: usize = 48;
 pub trait ASN1Decode {
     fn decode_sm2_public_key(&mut self) -> Option<PublicKey>;
     fn decode_sm2_cipher(&mut self)-> Option<Cipher::<PRE_MASTER_KEY_SIZE>>;
+    fn decode_sm2_ciphertext(&mut self)-> Option<Ciphertext>;
     fn decode_sm2_signature(&mut self)-> Option<Signature>;
 }
 
@@ -34,8 +47,8 @@ impl<'a> ASN1Decode for Parser<'a>{
         let y = parser.read_asn1_bigint()?;
         
         let mut cipher = Cipher::<PRE_MASTER_KEY_SIZE>{
-            x: bigint_to_u256(&x),
-            y: bigint_to_u256(&y),
+            x: bigint_to_u256(&x)?,
+            y: bigint_to_u256(&y)?,
             h: [0u8;sm3::DIGEST_SIZE],
             c: [0u8; PRE_MASTER_KEY_SIZE],
         };
@@ -51,41 +64,47 @@ impl<'a> ASN1Decode for Parser<'a>{
             return None;
         }
         cipher.c.copy_from_slice(c);
+        if !is_valid_c1(&cipher.x, &cipher.y) {
+            return None;
+        }
         Some(cipher)
     }    
 
-     // TODO: public key = BIT STRING = 04||x||y or ...
-     fn decode_sm2_public_key(&mut self) -> Option<PublicKey> {
-        let v = self.v;
-        if v.len() < 65 {
+    // decode a sm2 cipher with any length of C2.
+    fn decode_sm2_ciphertext(&mut self)-> Option<Ciphertext>{
+        let mut parser = self.read_asn1_sequence()?;
+        let x = parser.read_asn1_bigint()?;
+        let y = parser.read_asn1_bigint()?;
+
+        let hash = parser.read_asn1_octet_string()?;
+        if hash.len() != sm3::DIGEST_SIZE{
             return None;
         }
-        match v[0] {
-            4 => {
-                if v.len() != 65 {
-                    None
-                } else {
-                    let x = U256::from_be_slice(&v[1..33]).unwrap();
-                    let y = U256::from_be_slice(&v[33..65]).unwrap();
-                    let public_key = PublicKey { x, y };
-                    if !public_key.is_valid() {
-                        return None;
-                    }
-                    Some(public_key)
-                }
-            }
-            _ => {
-                panic!("unsupported public key format: {}", v[0])
-            }
+        let mut h = [0u8; sm3::DIGEST_SIZE];
+        h.copy_from_slice(hash);
+
+        let c = parser.read_asn1_octet_string()?.to_vec();
+        let (x, y) = (bigint_to_u256(&x)?, bigint_to_u256(&y)?);
+        if !is_valid_c1(&x, &y) {
+            return None;
         }
+        Some(Ciphertext{ x, y, h, c })
     }
+
+    // The public key is the content of a BIT STRING, in any of the SEC1 forms.
+    fn decode_sm2_public_key(&mut self) -> Option<PublicKey> {
+        PublicKey::from_sec1_bytes(self.v).ok()
+    }
+
     fn decode_sm2_signature(&mut self)-> Option<Signature>{
         let mut parser = self.read_asn1_sequence()?;
         let r = parser.read_asn1_bigint()?;
         let s = parser.read_asn1_bigint()?;
+        if !parser.empty() {
+            return None;
+        }
 
-        
-        Some(Signature{r: bigint_to_u256(&r), s: bigint_to_u256(&s)})
+        Some(Signature{r: bigint_to_u256(&r)?, s: bigint_to_u256(&s)?})
     }
 }
 
@@ -99,4 +118,235 @@ pub fn encode_sm2_cipher<const N:usize>(cipher: &Cipher<N>)-> Option<Vec<u8>> {
     });
     
     b.take().ok()
-}
\ No newline at end of file
+}
+
+pub fn encode_sm2_ciphertext(cipher: &Ciphertext)-> Option<Vec<u8>> {
+    let mut b = Builder::new(Vec::with_capacity(cipher.c.len() + 116));
+    b.add_asn1_sequence(|b| {
+        b.add_asn1_bigint(&u256_to_bigint(&cipher.x));
+        b.add_asn1_bigint(&u256_to_bigint(&cipher.y));
+        b.add_asn1_octet_string(&cipher.h);
+        b.add_asn1_octet_string(&cipher.c);
+    });
+
+    b.take().ok()
+}
+
+// The wire layouts of a SM2 ciphertext, C1 = 04 || x || y is the uncompressed point.
+// There is no reliable way to tell C1C3C2 from C1C2C3 by looking at the bytes,
+// so the caller must always say which one it expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiphertextFormat {
+    // 04 || x || y || C3 || C2, GB/T 32918.4-2016 and GM/T 0003-2012.
+    C1C3C2,
+    // 04 || x || y || C2 || C3, the draft standard, still used by old devices.
+    C1C2C3,
+    // SEQUENCE { x INTEGER, y INTEGER, hash OCTET STRING, cipher OCTET STRING }, GM/T 0009.
+    ASN1,
+}
+
+const UNCOMPRESSED_POINT_SIZE: usize = 65;
+const RAW_OVERHEAD: usize = UNCOMPRESSED_POINT_SIZE + sm3::DIGEST_SIZE;
+
+pub fn marshal_sm2_ciphertext(cipher: &Ciphertext, format: CiphertextFormat) -> Result<Vec<u8>> {
+    let c1c3 = |out: &mut Vec<u8>| {
+        out.push(4);
+        out.extend_from_slice(&cipher.x.to_be_bytes());
+        out.extend_from_slice(&cipher.y.to_be_bytes());
+    };
+    match format {
+        CiphertextFormat::C1C3C2 => {
+            let mut out = Vec::with_capacity(RAW_OVERHEAD + cipher.c.len());
+            c1c3(&mut out);
+            out.extend_from_slice(&cipher.h);
+            out.extend_from_slice(&cipher.c);
+            Ok(out)
+        }
+        CiphertextFormat::C1C2C3 => {
+            let mut out = Vec::with_capacity(RAW_OVERHEAD + cipher.c.len());
+            c1c3(&mut out);
+            out.extend_from_slice(&cipher.c);
+            out.extend_from_slice(&cipher.h);
+            Ok(out)
+        }
+        CiphertextFormat::ASN1 => encode_sm2_ciphertext(cipher).ok_or(Error::ASN1EncodingFailed),
+    }
+}
+
+pub fn unmarshal_sm2_ciphertext(data: &[u8], format: CiphertextFormat) -> Result<Ciphertext> {
+    match format {
+        CiphertextFormat::C1C3C2 | CiphertextFormat::C1C2C3 => {
+            if data.len() < RAW_OVERHEAD {
+                return Err(Error::CiphertextTooShort(RAW_OVERHEAD, data.len()));
+            }
+            if data[0] != 4 {
+                return Err(Error::UnsupportedPointFormat(data[0]));
+            }
+            let x = U256::from_be_slice(&data[1..33]).unwrap();
+            let y = U256::from_be_slice(&data[33..65]).unwrap();
+            let rest = &data[UNCOMPRESSED_POINT_SIZE..];
+            let (h, c) = if format == CiphertextFormat::C1C3C2 {
+                let (h, c) = rest.split_at(sm3::DIGEST_SIZE);
+                (h, c)
+            } else {
+                let (c, h) = rest.split_at(rest.len() - sm3::DIGEST_SIZE);
+                (h, c)
+            };
+            if !is_valid_c1(&x, &y) {
+                return Err(SM2Error::InvalidPoint.into());
+            }
+            Ok(Ciphertext { x, y, h: h.try_into().unwrap(), c: c.to_vec() })
+        }
+        CiphertextFormat::ASN1 => {
+            let mut input = Parser::new(data);
+            let mut parser = input.read_asn1_sequence().ok_or(Error::InvalidASN1Ciphertext)?;
+            let x = parser.read_asn1_bigint().ok_or(Error::InvalidASN1Ciphertext)?;
+            let y = parser.read_asn1_bigint().ok_or(Error::InvalidASN1Ciphertext)?;
+            let x = bigint_to_u256(&x).ok_or(Error::InvalidCoordinate)?;
+            let y = bigint_to_u256(&y).ok_or(Error::InvalidCoordinate)?;
+
+            let hash = parser.read_asn1_octet_string().ok_or(Error::InvalidASN1Ciphertext)?;
+            let h: [u8; sm3::DIGEST_SIZE] = hash
+                .try_into()
+                .map_err(|_| Error::InvalidHashLength(sm3::DIGEST_SIZE, hash.len()))?;
+            let c = parser.read_asn1_octet_string().ok_or(Error::InvalidASN1Ciphertext)?.to_vec();
+            if !parser.empty() {
+                return Err(Error::InvalidASN1Ciphertext);
+            }
+            if !input.empty() {
+                return Err(Error::TrailingData(input.len()));
+            }
+            if !is_valid_c1(&x, &y) {
+                return Err(SM2Error::InvalidPoint.into());
+            }
+            Ok(Ciphertext { x, y, h, c })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use rand::Rng;
+
+    #[test]
+    fn test_encode_sm2_ciphertext() {
+        let mut rng = rand::rng();
+        let sk = PrivateKey::new(&mut rng);
+        let pk = sk.public();
+
+        for n in [1, 16, 48, 200, 1000] {
+            let m = vec![0x5au8; n];
+            let cipher = encrypt_vec(&pk, &m, &rng.random()).unwrap();
+            let der = encode_sm2_ciphertext(&cipher).unwrap();
+
+            let decoded = Parser::new(&der).decode_sm2_ciphertext().unwrap();
+            assert_eq!(decoded, cipher);
+            assert_eq!(decrypt_vec(&sk, &decoded).unwrap(), m);
+        }
+    }
+
+    #[test]
+    fn test_ciphertext_formats() {
+        let mut rng = rand::rng();
+        let sk = PrivateKey::new(&mut rng);
+        let pk = sk.public();
+
+        for n in [0, 1, 32, 100] {
+            let m = vec![0xa5u8; n];
+            let cipher = encrypt_vec(&pk, &m, &rng.random()).unwrap();
+            for format in [CiphertextFormat::C1C3C2, CiphertextFormat::C1C2C3, CiphertextFormat::ASN1] {
+                let data = marshal_sm2_ciphertext(&cipher, format).unwrap();
+                let decoded = unmarshal_sm2_ciphertext(&data, format).unwrap();
+                assert_eq!(decoded, cipher);
+                assert_eq!(decrypt_vec(&sk, &decoded).unwrap(), m);
+            }
+
+            let c1c3c2 = marshal_sm2_ciphertext(&cipher, CiphertextFormat::C1C3C2).unwrap();
+            assert_eq!(&c1c3c2[65..97], &cipher.h);
+            let c1c2c3 = marshal_sm2_ciphertext(&cipher, CiphertextFormat::C1C2C3).unwrap();
+            assert_eq!(&c1c2c3[65 + n..], &cipher.h);
+        }
+    }
+
+    #[test]
+    fn test_ciphertext_format_errors() {
+        let short = [4u8; 96];
+        assert_eq!(
+            unmarshal_sm2_ciphertext(&short, CiphertextFormat::C1C3C2),
+            Err(Error::CiphertextTooShort(97, 96))
+        );
+        let mut raw = [0u8; 97];
+        raw[0] = 2;
+        assert_eq!(
+            unmarshal_sm2_ciphertext(&raw, CiphertextFormat::C1C2C3),
+            Err(Error::UnsupportedPointFormat(2))
+        );
+        assert_eq!(
+            unmarshal_sm2_ciphertext(&raw, CiphertextFormat::ASN1),
+            Err(Error::InvalidASN1Ciphertext)
+        );
+
+        let cipher = Ciphertext { x: U256 { v: [1, 0, 0, 0] }, y: U256 { v: [2, 0, 0, 0] }, h: [3; 32], c: vec![4; 5] };
+        let mut der = marshal_sm2_ciphertext(&cipher, CiphertextFormat::ASN1).unwrap();
+        assert_eq!(
+            unmarshal_sm2_ciphertext(&der, CiphertextFormat::ASN1),
+            Err(Error::SM2(SM2Error::InvalidPoint))
+        );
+        assert!(Parser::new(&der).decode_sm2_ciphertext().is_none());
+        der.push(0);
+        assert_eq!(
+            unmarshal_sm2_ciphertext(&der, CiphertextFormat::ASN1),
+            Err(Error::TrailingData(1))
+        );
+        for format in [CiphertextFormat::C1C3C2, CiphertextFormat::C1C2C3] {
+            let raw = marshal_sm2_ciphertext(&cipher, format).unwrap();
+            assert_eq!(
+                unmarshal_sm2_ciphertext(&raw, format),
+                Err(Error::SM2(SM2Error::InvalidPoint))
+            );
+        }
+
+        // SEQUENCE { INTEGER -1, INTEGER 2, OCTET STRING [3;32], OCTET STRING }
+        let mut der = hex!("30 2a 02 01 ff 02 01 02 04 20").to_vec();
+        der.extend_from_slice(&[3; 32]);
+        der.extend_from_slice(&hex!("04 00"));
+        assert_eq!(
+            unmarshal_sm2_ciphertext(&der, CiphertextFormat::ASN1),
+            Err(Error::InvalidCoordinate)
+        );
+        // hash with 31 bytes.
+        let mut der = hex!("30 29 02 01 01 02 01 02 04 1f").to_vec();
+        der.extend_from_slice(&[3; 31]);
+        der.extend_from_slice(&hex!("04 00"));
+        assert_eq!(
+            unmarshal_sm2_ciphertext(&der, CiphertextFormat::ASN1),
+            Err(Error::InvalidHashLength(32, 31))
+        );
+    }
+
+    #[test]
+    fn test_openssl_ciphertext() {
+        // generated by openssl pkeyutl -encrypt, which outputs GM/T 0009 form.
+        let der = hex!(
+            "308185022100983d80dad46b2b01fb0f4a9e0c7a56709350bba0d92a1ed30983a675c70e803b022100c17ceeaac295c9f1eb0aa3a354d5e0de32074eeabfa45fd3a8b1212430e6bbf30420c9df1efd7e40cc5b63fba136af21abece3bbe856079e141046bcc117775e05a8041b320d50f6c731d57d1bbbffa2bfbfaea0aceca662f22edba51e545e"
+        );
+        let cipher = unmarshal_sm2_ciphertext(&der, CiphertextFormat::ASN1).unwrap();
+        assert_eq!(cipher.x.to_be_bytes(), hex!("983d80dad46b2b01fb0f4a9e0c7a56709350bba0d92a1ed30983a675c70e803b"));
+        assert_eq!(cipher.c.len(), b"hello opengm sm2 encryption".len());
+        assert_eq!(marshal_sm2_ciphertext(&cipher, CiphertextFormat::ASN1).unwrap(), der);
+    }
+
+    #[test]
+    fn test_decode_sm2_signature() {
+        // SEQUENCE { INTEGER 1, INTEGER 2 }
+        let sig = Parser::new(&hex!("30 06 02 01 01 02 01 02")).decode_sm2_signature().unwrap();
+        assert_eq!(sig.r.v, [1, 0, 0, 0]);
+        assert_eq!(sig.s.v, [2, 0, 0, 0]);
+        // SEQUENCE { INTEGER 1, INTEGER 2, INTEGER 3 }
+        assert!(Parser::new(&hex!("30 09 02 01 01 02 01 02 02 01 03")).decode_sm2_signature().is_none());
+        // SEQUENCE { INTEGER 1 }
+        assert!(Parser::new(&hex!("30 03 02 01 01")).decode_sm2_signature().is_none());
+    }
+}
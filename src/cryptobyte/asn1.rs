@@ -6,6 +6,7 @@ pub mod integer;
 
 pub use object_identifier::*;
 pub use bit_string::*;
+pub use time::*;
 
 use super::Tag;
 
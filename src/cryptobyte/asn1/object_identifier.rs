@@ -17,7 +17,7 @@ const OID_MAX_SIZE:usize = 63;
 // An ObjectIdentifier represents an ASN.1 OBJECT IDENTIFIER.
 // There is only one way to initialize a ObjectIdentifier
 // let oid1 = 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct ObjectIdentifier{
     der: [u8; OID_MAX_SIZE],
     der_len: u8,
@@ -119,6 +119,7 @@ impl ObjectIdentifier {
             if part == 0{
                 der_data[der_data_len] = 0;
                 der_data_len += 1;
+                i += 1;
                 continue;
             }
 
@@ -154,7 +155,21 @@ impl ObjectIdentifier {
 impl ObjectIdentifier {
     // ObjectIdentifier from a DER encoding bytes.
     pub fn try_from_asn1(v: &[u8]) -> Result<Self> {
-        // TODO: check v
+        if v.is_empty() || v.len() > OID_MAX_SIZE {
+            return Err(Error::ASN1InvalidOidEncoding);
+        }
+        // every sub-identifier ends with a byte with the MSB cleared,
+        // and is encoded minimally, i.e., never starts with 0x80.
+        if v[v.len() - 1] & 0x80 != 0 {
+            return Err(Error::ASN1InvalidOidEncoding);
+        }
+        let mut start = true;
+        for x in v {
+            if start && *x == 0x80 {
+                return Err(Error::ASN1InvalidOidEncoding);
+            }
+            start = *x & 0x80 == 0;
+        }
         let mut oid = ObjectIdentifier::default();
         oid.der[..v.len()].copy_from_slice(v);
         oid.der_len = v.len() as u8;
@@ -195,5 +210,16 @@ mod tests {
         let a = vec![0x2au8, 0x86, 0x48,0x86,0xf7,0x0d];
         let oid = ObjectIdentifier::try_from_asn1(a.as_slice()).unwrap();
         assert_eq!("1.2.840.113549", oid.to_string());
+
+        assert!(ObjectIdentifier::try_from_asn1(&[]).is_err());
+        assert!(ObjectIdentifier::try_from_asn1(&[0x2a, 0x86]).is_err());
+        assert!(ObjectIdentifier::try_from_asn1(&[0x2a, 0x80, 0x01]).is_err());
+        assert!(ObjectIdentifier::try_from_asn1(&[0x2a; 64]).is_err());
+    }
+
+    #[test]
+    fn test_zero_part() {
+        let oid = ObjectIdentifier::from_slice(&[2, 16, 840, 1, 101, 3, 4, 0, 1]).unwrap();
+        assert_eq!("2.16.840.1.101.3.4.0.1", oid.to_string());
     }
 }
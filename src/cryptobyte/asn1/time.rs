@@ -1,3 +1,288 @@
-pub struct Time{
-    
-}
\ No newline at end of file
+use alloc::vec::Vec;
+use crate::cryptobyte::{Result, Error};
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+// Time is a civil time in UTC, the year is 0..=9999 as GeneralizedTime allows.
+// The fields are ordered so that the derived Ord is the chronological order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+}
+
+impl Time {
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<Time> {
+        if year > 9999 || month == 0 || month > 12 || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        // no leap seconds, as the Go's time package.
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+        Some(Time { year, month, day, hour, minute, second, nanosecond: 0 })
+    }
+
+    pub fn with_nanosecond(self, nanosecond: u32) -> Option<Time> {
+        if nanosecond >= 1_000_000_000 {
+            return None;
+        }
+        Some(Time { nanosecond, ..self })
+    }
+
+    pub fn year(&self) -> u16 { self.year }
+    pub fn month(&self) -> u8 { self.month }
+    pub fn day(&self) -> u8 { self.day }
+    pub fn hour(&self) -> u8 { self.hour }
+    pub fn minute(&self) -> u8 { self.minute }
+    pub fn second(&self) -> u8 { self.second }
+    pub fn nanosecond(&self) -> u32 { self.nanosecond }
+
+    // Returns the time of the seconds since 1970-01-01T00:00:00Z, None if the
+    // year is out of 0..=9999.
+    pub fn from_unix(secs: i64) -> Option<Time> {
+        let days = secs.div_euclid(SECONDS_PER_DAY);
+        let rem = secs.rem_euclid(SECONDS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        if !(0..=9999).contains(&year) {
+            return None;
+        }
+        Some(Time {
+            year: year as u16,
+            month,
+            day,
+            hour: (rem / 3600) as u8,
+            minute: (rem / 60 % 60) as u8,
+            second: (rem % 60) as u8,
+            nanosecond: 0,
+        })
+    }
+
+    // Returns the seconds since 1970-01-01T00:00:00Z, the nanoseconds are truncated.
+    pub fn unix(&self) -> i64 {
+        let days = days_from_civil(self.year as i64, self.month, self.day);
+        days * SECONDS_PER_DAY + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+
+    // UTCTime in DER: YYMMDDHHMMSSZ, YY >= 50 is 19YY and YY < 50 is 20YY.
+    pub fn parse_utc_time(v: &[u8]) -> Result<Time> {
+        if v.len() != 13 || v[12] != b'Z' {
+            return Err(Error::ASN1InvalidTime);
+        }
+        let yy = digits(&v[0..2])? as u16;
+        let year = if yy >= 50 { 1900 + yy } else { 2000 + yy };
+        Time::parse_date_time(year, &v[2..12])
+    }
+
+    // GeneralizedTime in DER: YYYYMMDDHHMMSS[.f]Z, the fraction has no
+    // trailing zeros and the seconds are always present.
+    pub fn parse_generalized_time(v: &[u8]) -> Result<Time> {
+        if v.len() < 15 || v[v.len() - 1] != b'Z' {
+            return Err(Error::ASN1InvalidTime);
+        }
+        let year = digits(&v[0..4])? as u16;
+        let t = Time::parse_date_time(year, &v[4..14])?;
+
+        let fraction = &v[14..v.len() - 1];
+        if fraction.is_empty() {
+            return Ok(t);
+        }
+        if fraction[0] != b'.' || fraction.len() < 2 || fraction.len() > 10 || fraction[fraction.len() - 1] == b'0' {
+            return Err(Error::ASN1InvalidTime);
+        }
+        let n = digits(&fraction[1..])?;
+        let nanosecond = n * 10u32.pow(10 - fraction.len() as u32);
+        t.with_nanosecond(nanosecond).ok_or(Error::ASN1InvalidTime)
+    }
+
+    // MMDDHHMMSS
+    fn parse_date_time(year: u16, v: &[u8]) -> Result<Time> {
+        let mut fields = [0u8; 5];
+        for (i, f) in fields.iter_mut().enumerate() {
+            *f = digits(&v[2 * i..2 * i + 2])? as u8;
+        }
+        let [month, day, hour, minute, second] = fields;
+        Time::new(year, month, day, hour, minute, second).ok_or(Error::ASN1InvalidTime)
+    }
+
+    // The nanoseconds are dropped, fails if the year is out of 1950..=2049.
+    pub fn to_utc_time(&self) -> Result<[u8; 13]> {
+        if !(1950..=2049).contains(&self.year) {
+            return Err(Error::ASN1InvalidTime);
+        }
+        let mut out = [0u8; 13];
+        put_digits(&mut out[0..2], (self.year % 100) as u32);
+        self.put_date_time(&mut out[2..12]);
+        out[12] = b'Z';
+        Ok(out)
+    }
+
+    pub fn to_generalized_time(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(25);
+        out.resize(14, 0);
+        put_digits(&mut out[0..4], self.year as u32);
+        self.put_date_time(&mut out[4..14]);
+        if self.nanosecond != 0 {
+            let mut fraction = [0u8; 9];
+            put_digits(&mut fraction, self.nanosecond);
+            let n = fraction.iter().rposition(|c| *c != b'0').unwrap() + 1;
+            out.push(b'.');
+            out.extend_from_slice(&fraction[..n]);
+        }
+        out.push(b'Z');
+        out
+    }
+
+    fn put_date_time(&self, out: &mut [u8]) {
+        let fields = [self.month, self.day, self.hour, self.minute, self.second];
+        for (i, f) in fields.iter().enumerate() {
+            put_digits(&mut out[2 * i..2 * i + 2], *f as u32);
+        }
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The days since 1970-01-01 of the proleptic Gregorian date, see
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// The inverse of days_from_civil.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn digits(v: &[u8]) -> Result<u32> {
+    let mut n = 0u32;
+    for c in v {
+        if !c.is_ascii_digit() {
+            return Err(Error::ASN1InvalidTime);
+        }
+        n = n * 10 + (c - b'0') as u32;
+    }
+    Ok(n)
+}
+
+fn put_digits(out: &mut [u8], mut n: u32) {
+    for c in out.iter_mut().rev() {
+        *c = b'0' + (n % 10) as u8;
+        n /= 10;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix() {
+        let tests: [(i64, (u16, u8, u8, u8, u8, u8)); 7] = [
+            (0, (1970, 1, 1, 0, 0, 0)),
+            (-1, (1969, 12, 31, 23, 59, 59)),
+            (951782400, (2000, 2, 29, 0, 0, 0)),
+            (1792309752, (2026, 10, 18, 7, 49, 12)),
+            (2524607999, (2049, 12, 31, 23, 59, 59)),
+            (-62167219200, (0, 1, 1, 0, 0, 0)),
+            (253402300799, (9999, 12, 31, 23, 59, 59)),
+        ];
+        for (secs, (y, mo, d, h, mi, s)) in tests {
+            let t = Time::new(y, mo, d, h, mi, s).unwrap();
+            assert_eq!(Time::from_unix(secs), Some(t), "{}", secs);
+            assert_eq!(t.unix(), secs);
+        }
+        assert_eq!(Time::from_unix(-62167219201), None);
+        assert_eq!(Time::from_unix(253402300800), None);
+    }
+
+    #[test]
+    fn test_utc_time() {
+        let tests: [(&[u8], Option<i64>); 12] = [
+            (b"261018074912Z", Some(1792309752)),
+            (b"500101000000Z", Some(-631152000)),
+            (b"491231235959Z", Some(2524607999)),
+            (b"000229000000Z", Some(951782400)),
+            (b"010229000000Z", None),
+            (b"261018074912", None),
+            (b"2610180749Z", None),
+            (b"261018074912+0800", None),
+            (b"261018074960Z", None),
+            (b"261318074912Z", None),
+            (b"26101807491aZ", None),
+            (b"2610180749-1Z", None),
+        ];
+        for (v, want) in tests {
+            let got = Time::parse_utc_time(v).ok();
+            assert_eq!(got.map(|t| t.unix()), want, "{}", core::str::from_utf8(v).unwrap());
+            if let Some(t) = got {
+                assert_eq!(&t.to_utc_time().unwrap(), v);
+            }
+        }
+        assert!(Time::new(1949, 12, 31, 23, 59, 59).unwrap().to_utc_time().is_err());
+        assert!(Time::new(2050, 1, 1, 0, 0, 0).unwrap().to_utc_time().is_err());
+    }
+
+    #[test]
+    fn test_generalized_time() {
+        let tests: [(&[u8], Option<(i64, u32)>); 11] = [
+            (b"20261018074912Z", Some((1792309752, 0))),
+            (b"20261018074912.5Z", Some((1792309752, 500_000_000))),
+            (b"20261018074912.123456789Z", Some((1792309752, 123_456_789))),
+            (b"99991231235959Z", Some((253402300799, 0))),
+            (b"19000228000000Z", Some((-2203977600, 0))),
+            (b"19000229000000Z", None),
+            (b"20261018074912.50Z", None),
+            (b"20261018074912.Z", None),
+            (b"20261018074912.1234567891Z", None),
+            (b"202610180749Z", None),
+            (b"20261018074912+0800", None),
+        ];
+        for (v, want) in tests {
+            let got = Time::parse_generalized_time(v).ok();
+            assert_eq!(got.map(|t| (t.unix(), t.nanosecond())), want, "{}", core::str::from_utf8(v).unwrap());
+            if let Some(t) = got {
+                assert_eq!(t.to_generalized_time(), v);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ord() {
+        let a = Time::parse_utc_time(b"491231235959Z").unwrap();
+        let b = Time::parse_utc_time(b"500101000000Z").unwrap();
+        assert!(b < a);
+        assert!(a < a.with_nanosecond(1).unwrap());
+    }
+}
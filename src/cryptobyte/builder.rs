@@ -4,7 +4,7 @@ use core::mem::{self, take};
 use alloc::vec::Vec;
 
 use super::*;
-use self::asn1::{BitString, ObjectIdentifier};
+use self::asn1::{BitString, ObjectIdentifier, Time};
 use num::bigint::{Sign, BigInt};
 
 // Builder is a rust version of golang.org/x/crypto/cryptobyte.
@@ -324,7 +324,17 @@ impl Builder {
         })
     }
 
-    // pub fn add_asn1_generalized_time(&mut self, t: time::SystemTime) {}
+    pub fn add_asn1_generalized_time(&mut self, t: &Time) {
+        self.add_asn1(GeneralizedTime, |b| b.add_bytes(&t.to_generalized_time()))
+    }
+
+    // add_asn1_utc_time fails if the year is out of 1950..=2049.
+    pub fn add_asn1_utc_time(&mut self, t: &Time) {
+        self.add_asn1(UTCTime, |b| match t.to_utc_time() {
+            Ok(v) => b.add_bytes(&v),
+            Err(e) => b.err = Some(e),
+        })
+    }
 
     // To encode the oid = [value1, value2, value3, ...], value1 = 0,1,2, value2 = 0..=39 if value1 is 0 or 1
     // The first octet has value 40 * value1 + value2. The following octets, if any, encode value3, ..., valuen.
@@ -484,4 +494,23 @@ mod tests {
         let b = parent.take().unwrap();
         assert_eq!(b, vec![6, 0x08, 0x2A, 0x81, 0x1C, 0xCF, 0x55, 0x01, 0x82, 0x2D]);
     }
+
+    #[test]
+    fn test_add_asn1_time() {
+        let t = Time::from_unix(1792309752).unwrap();
+        let mut b = Builder::new(Vec::new());
+        b.add_asn1_utc_time(&t);
+        b.add_asn1_generalized_time(&t.with_nanosecond(250_000_000).unwrap());
+        let der = b.take().unwrap();
+        assert_eq!(der, b"\x17\x0d261018074912Z\x18\x1220261018074912.25Z");
+
+        let mut p = Parser::new(&der);
+        assert_eq!(p.read_asn1_utc_time(), Some(t));
+        assert_eq!(p.read_asn1_generalized_time().map(|t| t.nanosecond()), Some(250_000_000));
+        assert!(p.empty());
+
+        let mut b = Builder::new(Vec::new());
+        b.add_asn1_utc_time(&Time::new(2050, 1, 1, 0, 0, 0).unwrap());
+        assert!(matches!(b.take(), Err(Error::ASN1InvalidTime)));
+    }
 }
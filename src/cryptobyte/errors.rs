@@ -26,6 +26,9 @@ pub enum Error {
     #[error("invalid BIT STRING padding")]
     ASN1InvalidBitStringPadding,
 
+    #[error("invalid UTCTime or GeneralizedTime")]
+    ASN1InvalidTime,
+
     #[error("unknown error")]
     Unknown,
 }
@@ -19,9 +19,21 @@ pub use parser::Parser;
 
 // use std::convert::*;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Tag(pub u8);
 
+impl Tag {
+    // Returns the tag with the constructed bit set.
+    pub const fn constructed(self) -> Tag {
+        Tag(self.0 | ClassConstructed)
+    }
+
+    // Returns the tag with the context-specific class set.
+    pub const fn context_specific(self) -> Tag {
+        Tag(self.0 | ClassContextSpecific)
+    }
+}
+
 impl From<Tag> for u8 {
     fn from(v: Tag) -> Self {
         v.0
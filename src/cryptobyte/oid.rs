@@ -25,7 +25,37 @@ pub const OidMGF1: ObjectIdentifier = oid!(1, 2, 840, 113549, 1, 1, 8);
 // but it's specified by ISO. Microsoft's makecert.exe has been known
 // to produce certificates with this OID.
 pub const OidISOSignatureSHA1WithRSA:ObjectIdentifier = oid!(1, 3, 14, 3, 2, 29);
-pub const OidSignatureSM2WithSM3: ObjectIdentifier = oid!(1, 2, 840, 10045, 2,1);
+pub const OidSignatureSM2WithSM3: ObjectIdentifier = oid!(1, 2, 156, 10197, 1, 501);
+
+// The public key algorithm of SM2 is id-ecPublicKey with the named curve sm2p256v1.
+pub const OidPublicKeyECDSA: ObjectIdentifier = oid!(1, 2, 840, 10045, 2, 1);
+pub const OidNamedCurveSM2: ObjectIdentifier = oid!(1, 2, 156, 10197, 1, 301);
+
+// GM/T 0006 algorithm identifiers.
+pub const OidSM3: ObjectIdentifier = oid!(1, 2, 156, 10197, 1, 401);
+pub const OidHMACWithSM3: ObjectIdentifier = oid!(1, 2, 156, 10197, 1, 401, 3, 1);
+pub const OidSM4CBC: ObjectIdentifier = oid!(1, 2, 156, 10197, 1, 104, 2);
+
+// RFC 8018 password-based encryption.
+pub const OidPBES2: ObjectIdentifier = oid!(1, 2, 840, 113549, 1, 5, 13);
+pub const OidPBKDF2: ObjectIdentifier = oid!(1, 2, 840, 113549, 1, 5, 12);
+
+// RFC 5280 attribute types of the distinguished names.
+pub const OidCommonName: ObjectIdentifier = oid!(2, 5, 4, 3);
+pub const OidSerialNumber: ObjectIdentifier = oid!(2, 5, 4, 5);
+pub const OidCountry: ObjectIdentifier = oid!(2, 5, 4, 6);
+pub const OidLocality: ObjectIdentifier = oid!(2, 5, 4, 7);
+pub const OidProvince: ObjectIdentifier = oid!(2, 5, 4, 8);
+pub const OidOrganization: ObjectIdentifier = oid!(2, 5, 4, 10);
+pub const OidOrganizationalUnit: ObjectIdentifier = oid!(2, 5, 4, 11);
+
+// RFC 5280 certificate extensions.
+pub const OidExtensionSubjectKeyId: ObjectIdentifier = oid!(2, 5, 29, 14);
+pub const OidExtensionKeyUsage: ObjectIdentifier = oid!(2, 5, 29, 15);
+pub const OidExtensionSubjectAltName: ObjectIdentifier = oid!(2, 5, 29, 17);
+pub const OidExtensionBasicConstraints: ObjectIdentifier = oid!(2, 5, 29, 19);
+pub const OidExtensionAuthorityKeyId: ObjectIdentifier = oid!(2, 5, 29, 35);
+pub const OidExtensionExtendedKeyUsage: ObjectIdentifier = oid!(2, 5, 29, 37);
 
 #[cfg(test)]
 mod tests {
@@ -1,7 +1,7 @@
 use core::ops::Index;
 use alloc::vec::Vec;
 
-use self::asn1::{ASN1Object, BitString, ObjectIdentifier};
+use self::asn1::{ASN1Object, BitString, ObjectIdentifier, Time};
 use num::bigint::BigInt;
 
 use super::*;
@@ -201,7 +201,7 @@ impl<'a> Parser<'a> {
 
     // helper function for String, read an ASN.1 element (tag < 30).
     // the return String include Tag and Length if skip_header = true.
-    pub fn read_asn1_object(&mut self) -> Option<ASN1Object> {
+    pub fn read_asn1_object(&mut self) -> Option<ASN1Object<'a>> {
         if self.v.len() < 2 {
             return None;
         }
@@ -246,7 +246,7 @@ impl<'a> Parser<'a> {
         })
     }
 
-    pub fn read_asn1(&mut self, tag: Tag) -> Option<&[u8]> {
+    pub fn read_asn1(&mut self, tag: Tag) -> Option<&'a [u8]> {
         let obj = self.read_asn1_object()?;
         if obj.tag != tag {
             return None;
@@ -254,6 +254,15 @@ impl<'a> Parser<'a> {
         Some(obj.value)
     }
 
+    // read an OPTIONAL element, returns Some(None) if the next element
+    // does not have the given tag (or there is no more element).
+    pub fn read_optional_asn1(&mut self, tag: Tag) -> Option<Option<&'a [u8]>> {
+        if self.peek_tag() != Some(tag) {
+            return Some(None);
+        }
+        Some(Some(self.read_asn1(tag)?))
+    }
+
     // // read an ASN.1 element's Value
     // pub fn read_any_asn1(&mut self) -> Option<(Tag, Parser)> {
     //     self.read_asn1_inner(true)
@@ -264,11 +273,11 @@ impl<'a> Parser<'a> {
     //     self.read_asn1_inner(false)
     // }
 
-    pub fn read_asn1_sequence(&mut self) -> Option<Parser>{
+    pub fn read_asn1_sequence(&mut self) -> Option<Parser<'a>>{
         Some(Parser::new(self.read_asn1(SEQUENCE)?))
     }
 
-    pub fn read_asn1_octet_string(&mut self) -> Option<&[u8]>{
+    pub fn read_asn1_octet_string(&mut self) -> Option<&'a [u8]>{
         let bytes = self.read_asn1(OCTET_STRING)?;
         Some(bytes)
     }
@@ -331,12 +340,14 @@ impl<'a> Parser<'a> {
         BitString::try_from(bytes).ok()
     }
 
-    pub fn read_asn1_generalized_time(&mut self) -> Option<()> {
-        todo!()
+    pub fn read_asn1_generalized_time(&mut self) -> Option<Time> {
+        let bytes = self.read_asn1(GeneralizedTime)?;
+        Time::parse_generalized_time(bytes).ok()
     }
 
-    pub fn read_asn1_utc_time(&mut self) -> Option<()> {
-        todo!()
+    pub fn read_asn1_utc_time(&mut self) -> Option<Time> {
+        let bytes = self.read_asn1(UTCTime)?;
+        Time::parse_utc_time(bytes).ok()
     }
 
 }
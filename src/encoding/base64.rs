@@ -0,0 +1,155 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use thiserror;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("base64: illegal byte {:#04x} at offset {}", .1, .0)]
+    InvalidByte(usize, u8),
+
+    #[error("base64: invalid length {}", .0)]
+    InvalidLength(usize),
+
+    #[error("base64: invalid padding")]
+    InvalidPadding,
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const PAD: u8 = b'=';
+const INVALID: u8 = 0xff;
+
+const DECODE_MAP: [u8; 256] = {
+    let mut map = [INVALID; 256];
+    let mut i = 0;
+    while i < 64 {
+        map[ALPHABET[i] as usize] = i as u8;
+        i += 1;
+    }
+    map
+};
+
+// Encodes src with the standard alphabet and padding.
+pub fn encode(src: &[u8]) -> String {
+    let mut out = String::with_capacity(src.len().div_ceil(3) * 4);
+    let (chunks, tail) = src.as_chunks::<3>();
+    for chunk in chunks {
+        let v = (chunk[0] as usize) << 16 | (chunk[1] as usize) << 8 | chunk[2] as usize;
+        out.push(ALPHABET[v >> 18] as char);
+        out.push(ALPHABET[v >> 12 & 0x3f] as char);
+        out.push(ALPHABET[v >> 6 & 0x3f] as char);
+        out.push(ALPHABET[v & 0x3f] as char);
+    }
+    match tail.len() {
+        1 => {
+            let v = (tail[0] as usize) << 16;
+            out.push(ALPHABET[v >> 18] as char);
+            out.push(ALPHABET[v >> 12 & 0x3f] as char);
+            out.push_str("==");
+        }
+        2 => {
+            let v = (tail[0] as usize) << 16 | (tail[1] as usize) << 8;
+            out.push(ALPHABET[v >> 18] as char);
+            out.push(ALPHABET[v >> 12 & 0x3f] as char);
+            out.push(ALPHABET[v >> 6 & 0x3f] as char);
+            out.push(PAD as char);
+        }
+        _ => {}
+    }
+    out
+}
+
+// Decodes src with the standard alphabet, the padding is required.
+// Whitespace (space, tab, CR and LF) is skipped, which makes the line
+// breaks of PEM and MIME harmless. The offsets in errors are of src.
+pub fn decode(src: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(src.len() / 4 * 3);
+    let mut quantum = [0u8; 4];
+    let mut n = 0;
+    let mut pads = 0;
+    let mut symbols = 0;
+    for (offset, c) in src.iter().enumerate() {
+        if matches!(c, b' ' | b'\t' | b'\r' | b'\n') {
+            continue;
+        }
+        symbols += 1;
+        if *c == PAD {
+            // the padding only appears in the last two positions of a quantum.
+            if n < 2 {
+                return Err(Error::InvalidPadding);
+            }
+            pads += 1;
+            quantum[n] = 0;
+        } else {
+            let v = DECODE_MAP[*c as usize];
+            if v == INVALID {
+                return Err(Error::InvalidByte(offset, *c));
+            }
+            // no data after the padding.
+            if pads > 0 {
+                return Err(Error::InvalidPadding);
+            }
+            quantum[n] = v;
+        }
+        n += 1;
+        if n == 4 {
+            let v = (quantum[0] as u32) << 18 | (quantum[1] as u32) << 12 | (quantum[2] as u32) << 6 | quantum[3] as u32;
+            let bytes = v.to_be_bytes();
+            // The unused bits of the last symbol before the padding must be zero.
+            let keep = 3 - pads;
+            if (pads == 1 && quantum[2] & 0x03 != 0) || (pads == 2 && quantum[1] & 0x0f != 0) {
+                return Err(Error::InvalidPadding);
+            }
+            out.extend_from_slice(&bytes[1..1 + keep]);
+            n = 0;
+        }
+    }
+    if n != 0 {
+        return Err(Error::InvalidLength(symbols));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VECTORS: [(&str, &str); 7] = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn test_rfc4648() {
+        for (plain, encoded) in VECTORS {
+            assert_eq!(encode(plain.as_bytes()), encoded);
+            assert_eq!(decode(encoded.as_bytes()).unwrap(), plain.as_bytes());
+        }
+        let data: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode(encode(&data).as_bytes()).unwrap(), data);
+    }
+
+    #[test]
+    fn test_decode_whitespace() {
+        assert_eq!(decode(b"Zm9v\r\nYmE=\n").unwrap(), b"fooba");
+        assert_eq!(decode(b" Zm 9v\tYg= =").unwrap(), b"foob");
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode(b"Zm9"), Err(Error::InvalidLength(3)));
+        assert_eq!(decode(b"Zm9*"), Err(Error::InvalidByte(3, b'*')));
+        assert_eq!(decode(b"Z==="), Err(Error::InvalidPadding));
+        assert_eq!(decode(b"Zg==Zm9v"), Err(Error::InvalidPadding));
+        assert_eq!(decode(b"Zm=v"), Err(Error::InvalidPadding));
+        // "Zh==" has non-zero unused bits.
+        assert_eq!(decode(b"Zh=="), Err(Error::InvalidPadding));
+        assert_eq!(decode(b"Zm9="), Err(Error::InvalidPadding));
+    }
+}
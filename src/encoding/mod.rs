@@ -0,0 +1,5 @@
+// Module encoding contains the text encodings of the binary objects,
+// i.e., Base64 of RFC 4648 and PEM of RFC 7468.
+
+pub mod base64;
+pub mod pem;
@@ -0,0 +1,174 @@
+// HKDF of RFC 5869 over HMAC, and the HKDF-Expand-Label and Derive-Secret
+// of TLS 1.3 (RFC 8446 7.1), for the SM suites of RFC 8998 with h = sm3::new.
+use alloc::vec::Vec;
+
+use super::{Error, Result};
+use crate::mac::HMac;
+use crate::sm3;
+use crate::traits::Hash;
+
+const LABEL_PREFIX: &[u8] = b"tls13 ";
+
+// HKDF-Extract: PRK = HMAC(salt, secret). An empty salt is the string of
+// N zeros, which is the same HMAC key.
+pub fn hkdf_extract<H: Hash<N>, const N: usize>(h: fn() -> H, secret: &[u8], salt: &[u8]) -> [u8; N] {
+    let mut mac = HMac::new_f(salt, h);
+    mac.write(secret);
+    mac.sum()
+}
+
+// HKDF-Expand: fills out with T(1) || T(2) || ..., T(i) = HMAC(prk, T(i-1) || info || i).
+// out is at most 255 * N bytes.
+pub fn hkdf_expand<H: Hash<N>, const N: usize>(h: fn() -> H, prk: &[u8], info: &[u8], out: &mut [u8]) -> Result<()> {
+    if out.len() > 255 * N {
+        return Err(Error::InvalidOutputLength(out.len(), 255 * N));
+    }
+    let mut mac = HMac::new_f(prk, h);
+    let mut t = [0u8; N];
+    for (i, chunk) in out.chunks_mut(N).enumerate() {
+        mac.reset();
+        if i > 0 {
+            mac.write(&t);
+        }
+        mac.write(info);
+        mac.write(&[i as u8 + 1]);
+        t = mac.sum();
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+    t.fill(0);
+    Ok(())
+}
+
+// HKDF: extract then expand.
+pub fn hkdf<H: Hash<N>, const N: usize>(
+    h: fn() -> H,
+    secret: &[u8],
+    salt: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<()> {
+    let mut prk = hkdf_extract(h, secret, salt);
+    let r = hkdf_expand(h, &prk, info, out);
+    prk.fill(0);
+    r
+}
+
+// HKDF-Expand-Label(secret, label, context, out.len()), the "tls13 " is
+// prepended to label here.
+pub fn hkdf_expand_label<H: Hash<N>, const N: usize>(
+    h: fn() -> H,
+    secret: &[u8],
+    label: &[u8],
+    context: &[u8],
+    out: &mut [u8],
+) -> Result<()> {
+    // struct {
+    //     uint16 length;
+    //     opaque label<7..255> = "tls13 " + Label;
+    //     opaque context<0..255> = Context;
+    // } HkdfLabel;
+    if LABEL_PREFIX.len() + label.len() > 255 || context.len() > 255 {
+        return Err(Error::InvalidLabel);
+    }
+    let mut info = Vec::with_capacity(4 + LABEL_PREFIX.len() + label.len() + context.len());
+    info.extend_from_slice(&(out.len() as u16).to_be_bytes());
+    info.push((LABEL_PREFIX.len() + label.len()) as u8);
+    info.extend_from_slice(LABEL_PREFIX);
+    info.extend_from_slice(label);
+    info.push(context.len() as u8);
+    info.extend_from_slice(context);
+    hkdf_expand(h, secret, &info, out)
+}
+
+// Derive-Secret(secret, label, messages), transcript is the hash of the
+// messages so far.
+pub fn derive_secret<H: Hash<N>, const N: usize>(
+    h: fn() -> H,
+    secret: &[u8],
+    label: &[u8],
+    transcript: &H,
+) -> Result<[u8; N]> {
+    let mut out = [0u8; N];
+    hkdf_expand_label(h, secret, label, &transcript.sum(), &mut out)?;
+    Ok(out)
+}
+
+pub fn hkdf_sm3_extract(secret: &[u8], salt: &[u8]) -> [u8; sm3::DIGEST_SIZE] {
+    hkdf_extract(sm3::new, secret, salt)
+}
+
+pub fn hkdf_sm3_expand(prk: &[u8], info: &[u8], out: &mut [u8]) -> Result<()> {
+    hkdf_expand(sm3::new, prk, info, out)
+}
+
+pub fn hkdf_sm3(secret: &[u8], salt: &[u8], info: &[u8], out: &mut [u8]) -> Result<()> {
+    hkdf(sm3::new, secret, salt, info, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use std::vec::Vec;
+
+    // the inputs of RFC 5869 A.1-A.3, computed against SM3 by python hmac.
+    #[test]
+    fn test_hkdf_sm3() {
+        let ikm = [0x0b; 22];
+        let prk = hkdf_sm3_extract(&ikm, &hex!("000102030405060708090a0b0c"));
+        assert_eq!(prk, hex!("e0d6f7b0bd056327b7659f1f39ad850561fbcf4fb10fb58e88eafa55cf7cd01e"));
+        let mut okm = [0u8; 42];
+        hkdf_sm3_expand(&prk, &hex!("f0f1f2f3f4f5f6f7f8f9"), &mut okm).unwrap();
+        assert_eq!(okm, hex!("c69fe91b7aaee2dd5718d72dcaee0cce93f1b8e41f792da51261b6a517e68b36ed2c595572b01dfa359b"));
+
+        let ikm: Vec<u8> = (0x00..0x50).collect();
+        let salt: Vec<u8> = (0x60..0xb0).collect();
+        let info: Vec<u8> = (0xb0..=0xff).collect();
+        let prk = hkdf_sm3_extract(&ikm, &salt);
+        assert_eq!(prk, hex!("1a43a7fedb2d111eb33babd0d256c272aa3262cdb12e6b43d4321ae8888485d5"));
+        let mut okm = [0u8; 82];
+        hkdf_sm3(&ikm, &salt, &info, &mut okm).unwrap();
+        assert_eq!(okm, hex!("
+            c1226236bbdefa7921f9febe27b864f33e449201b436d8844ea53f58170dd642
+            6defbd22ed1f3c5960f35523e62e3b6c0d657f2c61893436f539013199bfaef2
+            5aafd1e7726ede927623a9f5cbb8885c7e5d"));
+
+        let ikm = [0x0b; 22];
+        let prk = hkdf_sm3_extract(&ikm, &[]);
+        assert_eq!(prk, hex!("004fc37143377d072d74e82ff480e8d7937ec607411bc1ec65dd34401871ff9c"));
+        assert_eq!(hkdf_sm3_extract(&ikm, &[0; 32]), prk);
+        let mut okm = [0u8; 42];
+        hkdf_sm3(&ikm, &[], &[], &mut okm).unwrap();
+        assert_eq!(okm, hex!("c8c91a38ae2fb3b023a7c38ce9f0748f28230d59b6b950ba3ba949bf0d713a5774815778801741cb2034"));
+    }
+
+    #[test]
+    fn test_hkdf_output_length() {
+        let prk = [1u8; 32];
+        let mut okm = vec![0u8; 255 * 32];
+        hkdf_sm3_expand(&prk, b"info", &mut okm).unwrap();
+        let mut short = [0u8; 100];
+        hkdf_sm3_expand(&prk, b"info", &mut short).unwrap();
+        assert_eq!(short, okm[..100]);
+
+        let mut okm = vec![0u8; 255 * 32 + 1];
+        assert!(matches!(hkdf_sm3_expand(&prk, b"info", &mut okm), Err(Error::InvalidOutputLength(8161, 8160))));
+    }
+
+    #[test]
+    fn test_tls13_key_schedule() {
+        // the early secret without PSK of TLS 1.3 with SM3.
+        let early_secret = hkdf_sm3_extract(&[0; 32], &[]);
+        assert_eq!(early_secret, hex!("a4f50a29c327e9acc4ddd4dbe32b75a6a1d77e4bbe823e3d71fdcc1a5fa52757"));
+        let derived = derive_secret(sm3::new, &early_secret, b"derived", &sm3::Digest::new()).unwrap();
+        assert_eq!(derived, hex!("8bf1d43b3cb61da421895be55c07b3c1f49d7af9f9c728240cee1fc8039252f4"));
+
+        let mut key = [0u8; 16];
+        hkdf_expand_label(sm3::new, &early_secret, b"key", &[], &mut key).unwrap();
+        assert_eq!(key, hex!("bdfd37433be3bdf3010d59595cd405a2"));
+
+        let long_label = [b'a'; 250];
+        assert!(matches!(hkdf_expand_label(sm3::new, &early_secret, &long_label, &[], &mut key), Err(Error::InvalidLabel)));
+        assert!(matches!(hkdf_expand_label(sm3::new, &early_secret, b"key", &[0; 256], &mut key), Err(Error::InvalidLabel)));
+    }
+}
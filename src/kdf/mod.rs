@@ -0,0 +1,23 @@
+// Module kdf contains the key derivation functions built on SM3.
+
+pub mod hkdf;
+pub mod pbkdf2;
+pub mod sm3_kdf;
+pub use hkdf::*;
+pub use pbkdf2::*;
+pub use sm3_kdf::*;
+use thiserror;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("invalid output length {}, at most {}", .0, .1)]
+    InvalidOutputLength(usize, usize),
+
+    #[error("label or context longer than 255 bytes")]
+    InvalidLabel,
+
+    #[error("the iteration count must be positive")]
+    InvalidIterationCount,
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,86 @@
+use super::{Error, Result};
+use crate::mac::HMac;
+use crate::sm3;
+use crate::traits::Hash;
+
+// PBKDF2 of RFC 8018 with the HMAC over h as the PRF, fills out with the derived key.
+// T_i = U_1 ^ U_2 ^ ... ^ U_c, U_1 = PRF(P, S || INT(i)), U_j = PRF(P, U_{j-1}).
+// The PRF keyed by the password is cloned for each U_j, the ipad and the opad
+// are hashed only once. The iteration count c is positive.
+pub fn pbkdf2<H: Hash<N> + Clone, const N: usize>(
+    h: fn() -> H,
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out: &mut [u8],
+) -> Result<()> {
+    if iterations == 0 {
+        return Err(Error::InvalidIterationCount);
+    }
+    let prf = HMac::new_f(password, h);
+    for (i, chunk) in out.chunks_mut(N).enumerate() {
+        let mut m = prf.clone();
+        m.write(salt);
+        m.write(&(i as u32 + 1).to_be_bytes());
+        let mut u = m.sum();
+        let mut t = u;
+        for _ in 1..iterations {
+            let mut m = prf.clone();
+            m.write(&u);
+            u = m.sum();
+            for (t, u) in t.iter_mut().zip(u.iter()) {
+                *t ^= u;
+            }
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+        t.fill(0);
+        u.fill(0);
+    }
+    Ok(())
+}
+
+pub fn pbkdf2_hmac_sm3(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) -> Result<()> {
+    pbkdf2(sm3::new, password, salt, iterations, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    // computed by python hashlib.pbkdf2_hmac("sm3", ...).
+    #[test]
+    fn test_pbkdf2_hmac_sm3() {
+        let mut out = [0u8; 32];
+        pbkdf2_hmac_sm3(b"password", b"salt", 1, &mut out).unwrap();
+        assert_eq!(out, hex!("4612f922a1fdcefaf4312fc6f8f3322b489cbf24f2ea361b44c2bd8fa2c6dcb0"));
+
+        let mut out = [0u8; 40];
+        pbkdf2_hmac_sm3(b"password", b"salt", 4096, &mut out).unwrap();
+        assert_eq!(out, hex!("b6e8f2074c87432b78f62e5ced980fdff89e86af2f693dab1638e2b3683045dd844438500eead50c"));
+
+        // the inputs of RFC 6070.
+        let mut out = [0u8; 40];
+        pbkdf2_hmac_sm3(b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096, &mut out).unwrap();
+        assert_eq!(out, hex!("3b6282ac8519f059e465abff0ea37b0dbfe6c672a76e6b805312d53900db630732ccc1a88fa5512a"));
+
+        let mut out = [0u8; 16];
+        pbkdf2_hmac_sm3(b"pass\0word", b"sa\0lt", 4096, &mut out).unwrap();
+        assert_eq!(out, hex!("5f936b2e356f06e2bb3932165821261c"));
+
+        // a password longer than the block is hashed first.
+        let mut out = [0u8; 70];
+        pbkdf2(sm3::new, &[b'k'; 100], b"salt", 2, &mut out).unwrap();
+        assert_eq!(out, hex!("
+            601bb452a1477bf1be894c5deb2987fa77eceaa9b07211b2f4c41de0e6a938a9
+            42e62f17aff4e42efb79e6e3a1cbe9529c6b60a6b6710d586af594f0e09acccb
+            d316dcc584c9"));
+    }
+
+    #[test]
+    fn test_pbkdf2_iterations() {
+        let mut out = [0u8; 32];
+        assert!(matches!(pbkdf2_hmac_sm3(b"password", b"salt", 0, &mut out), Err(Error::InvalidIterationCount)));
+        assert_eq!(out, [0; 32]);
+    }
+}
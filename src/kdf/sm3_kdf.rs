@@ -0,0 +1,134 @@
+// The KDF of GB/T 32918.4 (GM/T 0003.4) 5.4.3, used by SM2 encryption, SM2
+// key exchange and SM9: K = Hash(Z || ct_1) || Hash(Z || ct_2) || ..., ct_i = i
+// as a 32 bits big-endian counter. At most (2^32 - 1) hash lengths are derived.
+use super::{Error, Result};
+use crate::sm3;
+
+const DIGEST_SIZE: usize = sm3::DIGEST_SIZE;
+const MAX_COUNTER: u64 = u32::MAX as u64;
+
+// Sm3Kdf hashes the shared secret Z, written in one or more pieces.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sm3Kdf {
+    hash: sm3::Digest,
+}
+
+impl Sm3Kdf {
+    pub fn new() -> Sm3Kdf {
+        Sm3Kdf { hash: sm3::Digest::new() }
+    }
+
+    pub fn write(&mut self, z: &[u8]) -> &mut Self {
+        self.hash.write(z);
+        self
+    }
+
+    // Returns a reader of the key derived from the Z written so far.
+    pub fn reader(&self) -> Sm3KdfReader {
+        Sm3KdfReader { hash: self.hash, ct: 1, block: [0; DIGEST_SIZE], pos: DIGEST_SIZE }
+    }
+}
+
+// Sm3KdfReader reads the derived key in any number of calls, the key read in
+// pieces is the same as in one call.
+#[derive(Debug, Clone)]
+pub struct Sm3KdfReader {
+    hash: sm3::Digest,
+    // the counter of the next block.
+    ct: u64,
+    // the block of ct - 1, block[pos..] are not read yet.
+    block: [u8; DIGEST_SIZE],
+    pos: usize,
+}
+
+impl Sm3KdfReader {
+    // Returns the number of bytes left to read.
+    pub fn remaining(&self) -> u64 {
+        (MAX_COUNTER + 1 - self.ct) * DIGEST_SIZE as u64 + (DIGEST_SIZE - self.pos) as u64
+    }
+
+    // Fills out with the next out.len() bytes of the key. Nothing is read if
+    // the key left is shorter than out.
+    pub fn read(&mut self, out: &mut [u8]) -> Result<()> {
+        if out.len() as u64 > self.remaining() {
+            return Err(Error::InvalidOutputLength(out.len(), self.remaining().try_into().unwrap_or(usize::MAX)));
+        }
+        let mut out = out;
+        while !out.is_empty() {
+            if self.pos == DIGEST_SIZE {
+                let mut h = self.hash;
+                h.write(&(self.ct as u32).to_be_bytes());
+                h.sum_into(&mut self.block);
+                self.ct += 1;
+                self.pos = 0;
+            }
+            let n = out.len().min(DIGEST_SIZE - self.pos);
+            out[..n].copy_from_slice(&self.block[self.pos..self.pos + n]);
+            self.pos += n;
+            out = &mut out[n..];
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Sm3KdfReader {
+    fn drop(&mut self) {
+        self.block.fill(0);
+    }
+}
+
+// Fills out with the key derived from z.
+pub fn sm3_kdf(z: &[u8], out: &mut [u8]) -> Result<()> {
+    Sm3Kdf::new().write(z).reader().read(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_sm3_kdf() {
+        // the example of GB/T 32918.4 A.2, Z = x2 || y2, klen = 152.
+        let x2 = hex!("64D20D27D0632957F8028C1E024F6B02EDF23102A566C932AE8BD613A8E865FE");
+        let y2 = hex!("58D225ECA784AE300A81A2D48281A828E1CEDF11C4219099840265375077BF78");
+        let mut t = [0u8; 19];
+        Sm3Kdf::new().write(&x2).write(&y2).reader().read(&mut t).unwrap();
+        assert_eq!(t, hex!("006E30DAE231B071DFAD8AA379E90264491603"));
+
+        let wanted = hex!("
+            fe1ea80dac6f100c33537bd24619ec7c72a1e8b1ffeaefb1eb52a37791fdaf61
+            9db16c0ac7bebb47238c6cc925ff66af7936e278e12d2664502bb38b03fd41cb
+            2975a660d33ecc32fe62f27c738964e266ec71694f39a68810af5a05d3b45d67
+            975866a5");
+        let mut out = [0u8; 100];
+        sm3_kdf(b"abc", &mut out).unwrap();
+        assert_eq!(out, wanted);
+
+        // any split of the reads.
+        for chunk in [1, 7, 31, 32, 33, 64] {
+            let mut r = Sm3Kdf::new().write(b"abc").reader();
+            let mut out = [0u8; 100];
+            out.chunks_mut(chunk).for_each(|c| r.read(c).unwrap());
+            assert_eq!(out, wanted, "{}", chunk);
+        }
+    }
+
+    #[test]
+    fn test_sm3_kdf_limit() {
+        let mut r = Sm3Kdf::new().write(b"z").reader();
+        assert_eq!(r.remaining(), MAX_COUNTER * DIGEST_SIZE as u64);
+
+        // the last block, ct = 2^32 - 1.
+        r.ct = MAX_COUNTER;
+        assert_eq!(r.remaining(), DIGEST_SIZE as u64);
+        let mut out = [0u8; 40];
+        assert!(matches!(r.read(&mut out), Err(Error::InvalidOutputLength(40, 32))));
+        r.read(&mut out[..10]).unwrap();
+        r.read(&mut out[10..32]).unwrap();
+        assert_eq!(out[..32], hex!("6eb24744b1e119642fee4d12f66ec4908259746e746733a7e3fa246232d0f425"));
+        assert_eq!(r.remaining(), 0);
+        assert!(r.read(&mut out[..1]).is_err());
+        r.read(&mut []).unwrap();
+    }
+}
@@ -34,6 +34,9 @@ pub mod blockmode;
 pub mod mac;
 pub mod traits;
 pub mod cryptobyte;
+pub mod x509;
+pub mod kdf;
+pub mod encoding;
 
 // the non-GMT algorithm: SHA256, AES, and RSA.
 pub mod x;
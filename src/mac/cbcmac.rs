@@ -0,0 +1,193 @@
+// The block cipher MACs of GB/T 15852.1-2020 (ISO/IEC 9797-1:2011), all
+// based on CBC-MAC and differ in the initial and the final iteration.
+// The keys K, K' and K'' are independent, the MAC is the leftmost bits of
+// the output as needed.
+use alloc::vec::Vec;
+
+use super::cmac::CMac;
+use crate::traits::Block;
+
+const BLOCK_SIZE: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MacPadding {
+    // zeros, nothing if the data is aligned, one block of zeros if the data is empty.
+    Method1,
+    // 0x80 then zeros, always at least one byte.
+    Method2,
+    // a block of the bit length of the data in big-endian, then the data
+    // padded as method 1 except that the empty data is not padded.
+    Method3,
+}
+
+impl MacPadding {
+    fn pad(&self, data: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(data.len() + 2 * BLOCK_SIZE);
+        match self {
+            MacPadding::Method1 => {
+                out.extend_from_slice(data);
+                if data.is_empty() {
+                    out.resize(BLOCK_SIZE, 0);
+                }
+            }
+            MacPadding::Method2 => {
+                out.extend_from_slice(data);
+                out.push(0x80);
+            }
+            MacPadding::Method3 => {
+                out.extend_from_slice(&(data.len() as u128 * 8).to_be_bytes());
+                out.extend_from_slice(data);
+            }
+        }
+        out.resize(out.len().next_multiple_of(BLOCK_SIZE), 0);
+        out
+    }
+}
+
+// H_i = E_K(H_{i-1} ^ D_i), in place on h.
+fn cbc<B: Block>(k: &B, h: &mut [u8; BLOCK_SIZE], blocks: &[u8]) {
+    for d in blocks.chunks_exact(BLOCK_SIZE) {
+        h.iter_mut().zip(d).for_each(|(h, d)| *h ^= *d);
+        k.encrypt_inplace(h);
+    }
+}
+
+// MAC algorithm 1: the CBC-MAC, G = H_q.
+pub fn mac_algorithm1<B: Block>(k: &B, data: &[u8], padding: MacPadding) -> [u8; BLOCK_SIZE] {
+    debug_assert_eq!(k.block_size(), BLOCK_SIZE);
+    let mut h = [0u8; BLOCK_SIZE];
+    cbc(k, &mut h, &padding.pad(data));
+    h
+}
+
+// MAC algorithm 2: G = E_K'(H_q).
+pub fn mac_algorithm2<B: Block>(k: &B, k1: &B, data: &[u8], padding: MacPadding) -> [u8; BLOCK_SIZE] {
+    let mut h = mac_algorithm1(k, data, padding);
+    k1.encrypt_inplace(&mut h);
+    h
+}
+
+// MAC algorithm 3, the ANSI X9.19 retail MAC: G = E_K(D_K'(H_q)).
+pub fn mac_algorithm3<B: Block>(k: &B, k1: &B, data: &[u8], padding: MacPadding) -> [u8; BLOCK_SIZE] {
+    let mut h = mac_algorithm1(k, data, padding);
+    k1.decrypt_inplace(&mut h);
+    k.encrypt_inplace(&mut h);
+    h
+}
+
+// MAC algorithm 4: H_1 = E_K''(E_K(D_1)), G = E_K'(H_q).
+// Returns None if the padded data has less than two blocks.
+pub fn mac_algorithm4<B: Block>(
+    k: &B,
+    k1: &B,
+    k2: &B,
+    data: &[u8],
+    padding: MacPadding,
+) -> Option<[u8; BLOCK_SIZE]> {
+    debug_assert_eq!(k.block_size(), BLOCK_SIZE);
+    let padded = padding.pad(data);
+    if padded.len() < 2 * BLOCK_SIZE {
+        return None;
+    }
+    let (first, rest) = padded.split_at(BLOCK_SIZE);
+    let mut h: [u8; BLOCK_SIZE] = first.try_into().unwrap();
+    k.encrypt_inplace(&mut h);
+    k2.encrypt_inplace(&mut h);
+    cbc(k, &mut h, rest);
+    k1.encrypt_inplace(&mut h);
+    Some(h)
+}
+
+// MAC algorithm 5, the CMAC, the padding is its own.
+pub fn mac_algorithm5<B: Block>(k: B, data: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut m = CMac::new(k);
+    m.write(data);
+    m.sum()
+}
+
+// MAC algorithm 6, the LMAC: G = E_K'(H_{q-1} ^ D_q).
+pub fn mac_algorithm6<B: Block>(k: &B, k1: &B, data: &[u8], padding: MacPadding) -> [u8; BLOCK_SIZE] {
+    debug_assert_eq!(k.block_size(), BLOCK_SIZE);
+    let padded = padding.pad(data);
+    let (rest, last) = padded.split_at(padded.len() - BLOCK_SIZE);
+    let mut h = [0u8; BLOCK_SIZE];
+    cbc(k, &mut h, rest);
+    h.iter_mut().zip(last).for_each(|(h, d)| *h ^= *d);
+    k1.encrypt_inplace(&mut h);
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sm4::Cipher;
+    use hex_literal::hex;
+
+    const MSG: &[u8; 64] = b"AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDD";
+
+    #[test]
+    fn test_padding() {
+        assert_eq!(MacPadding::Method1.pad(&[]), [0; 16]);
+        assert_eq!(MacPadding::Method1.pad(&[1; 16]), [1; 16]);
+        assert_eq!(MacPadding::Method2.pad(&[1; 16]).len(), 32);
+        assert_eq!(MacPadding::Method3.pad(&[]), [0; 16]);
+        assert_eq!(MacPadding::Method3.pad(&[1; 2]), hex!("0000000000000000000000000000001001010000000000000000000000000000"));
+    }
+
+    #[test]
+    fn test_mac_algorithms() {
+        // printed by scripts/sm4_cbcmac_vectors.py.
+        let k = Cipher::new(&hex!("0123456789ABCDEFFEDCBA9876543210"));
+        let k1 = Cipher::new(&hex!("FEDCBA98765432100123456789ABCDEF"));
+        let k2 = Cipher::new(&hex!("00112233445566778899AABBCCDDEEFF"));
+        let tests: [(usize, MacPadding, [[u8; 16]; 4], Option<[u8; 16]>); 6] = [
+            (0, MacPadding::Method1, [
+                hex!("2677f46b09c122cc975533105bd4a22a"),
+                hex!("9fb09a3987dad890df3065f01bb587d4"),
+                hex!("c17dec089dec65b3b6ebd355b62f3990"),
+                hex!("400133569e9cc52a4d9321cd2221550f"),
+            ], None),
+            (0, MacPadding::Method2, [
+                hex!("8c338e5a27e349beae39214feda97099"),
+                hex!("2d461797ed9bc9bf0c1cc829f3e1374b"),
+                hex!("0f64284e14a6b71446c3366506468d40"),
+                hex!("455346136de56a09a3b1a33ae641e709"),
+            ], None),
+            (16, MacPadding::Method1, [
+                hex!("99ce75c0ca2949d3eb87bd2d831f3510"),
+                hex!("df3094fe629a69e7b0e17aae5702e9b1"),
+                hex!("135322d5ffd239c251ea6c5014846aca"),
+                hex!("796d85b75407484efb6faed7b943231e"),
+            ], None),
+            (16, MacPadding::Method2, [
+                hex!("cd09cce8d9611c225e5f8821c3c4e9c2"),
+                hex!("fce918961605bb37e71af15c00d46b43"),
+                hex!("005d5b5b70325852454d43c960ca199c"),
+                hex!("37d5b6ab631282d6d20570a7b835fac4"),
+            ], Some(hex!("4097732b4d6162ef8d978d0427e88443"))),
+            (20, MacPadding::Method1, [
+                hex!("6ee2a501d702419189741e1c103dc1d4"),
+                hex!("630e73c7b4773b64b1b1424e24a43d18"),
+                hex!("5d1e32f70c72eb0ad59dd88c1479305a"),
+                hex!("f70feae23018984c33dd24bb0e244998"),
+            ], Some(hex!("a3af24c49a66791cff1ff669a7fb8dd8"))),
+            (20, MacPadding::Method3, [
+                hex!("d7f872fc8fe5cacce936fd4a8158f026"),
+                hex!("d14209be44bdeb4d608ad979d9162126"),
+                hex!("b9d85f5b23a1cf5c326c47c250ca9454"),
+                hex!("692164fb9de573bf31d803673dc93dec"),
+            ], Some(hex!("b0a419c0097d0fb577099cc8d598ad71"))),
+        ];
+        for (n, padding, [m1, m2, m3, m6], m4) in tests {
+            let data = &MSG[..n];
+            assert_eq!(mac_algorithm1(&k, data, padding), m1, "{} {:?}", n, padding);
+            assert_eq!(mac_algorithm2(&k, &k1, data, padding), m2, "{} {:?}", n, padding);
+            assert_eq!(mac_algorithm3(&k, &k1, data, padding), m3, "{} {:?}", n, padding);
+            assert_eq!(mac_algorithm4(&k, &k1, &k2, data, padding), m4, "{} {:?}", n, padding);
+            assert_eq!(mac_algorithm6(&k, &k1, data, padding), m6, "{} {:?}", n, padding);
+        }
+
+        // openssl mac -cipher SM4-CBC CMAC
+        assert_eq!(mac_algorithm5(&k, &MSG[..20]), hex!("8A523F5BDAECCCCE207C6FCDFC6FBD24"));
+    }
+}
@@ -0,0 +1,191 @@
+use subtle::ConstantTimeEq;
+
+use crate::sm4;
+use crate::traits::Block;
+
+const BLOCK_SIZE: usize = 16;
+// x^128 + x^7 + x^2 + x + 1
+const RB: u8 = 0x87;
+// The shortest MAC accepted by verify_truncated, see SP 800-38B, Appendix A.
+pub const CMAC_MIN_TRUNCATED_SIZE: usize = 8;
+
+// CMac is the CMAC of NIST SP 800-38B (OMAC1), also the MAC algorithm 5 of
+// GB/T 15852.1-2020 and ISO/IEC 9797-1.
+pub struct CMac<B: Block> {
+    block: B,
+    k1: [u8; BLOCK_SIZE],
+    k2: [u8; BLOCK_SIZE],
+    x: [u8; BLOCK_SIZE],
+    // the last block is kept until more data comes, it may be the final one.
+    buf: [u8; BLOCK_SIZE],
+    pos: usize,
+}
+
+pub type Sm4Cmac = CMac<sm4::Cipher>;
+
+// The input must have length 16.
+pub fn new_sm4_cmac(key: &[u8]) -> Sm4Cmac {
+    CMac::new(sm4::Cipher::new(&key))
+}
+
+pub fn sm4_cmac(key: &[u8], data: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut m = new_sm4_cmac(key);
+    m.write(data);
+    m.sum()
+}
+
+impl<B: Block> CMac<B> {
+    pub fn new(block: B) -> Self {
+        debug_assert_eq!(block.block_size(), BLOCK_SIZE);
+        let mut l = [0u8; BLOCK_SIZE];
+        block.encrypt_inplace(&mut l);
+        let k1 = dbl(&l);
+        let k2 = dbl(&k1);
+        l.fill(0);
+        CMac {
+            block,
+            k1,
+            k2,
+            x: [0; BLOCK_SIZE],
+            buf: [0; BLOCK_SIZE],
+            pos: 0,
+        }
+    }
+
+    // reset CMac for next computation, use the same key.
+    pub fn reset(&mut self) {
+        self.x.fill(0);
+        self.buf.fill(0);
+        self.pos = 0;
+    }
+
+    pub fn write(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.pos == BLOCK_SIZE {
+                self.x.iter_mut().zip(&self.buf).for_each(|(x, b)| *x ^= *b);
+                self.block.encrypt_inplace(&mut self.x);
+                self.pos = 0;
+            }
+            let n = (BLOCK_SIZE - self.pos).min(data.len());
+            self.buf[self.pos..self.pos + n].copy_from_slice(&data[..n]);
+            self.pos += n;
+            data = &data[n..];
+        }
+    }
+
+    // Returns the MAC of the data written so far, more data can be written after.
+    pub fn sum(&self) -> [u8; BLOCK_SIZE] {
+        let mut last = [0u8; BLOCK_SIZE];
+        last[..self.pos].copy_from_slice(&self.buf[..self.pos]);
+        let k = if self.pos == BLOCK_SIZE {
+            &self.k1
+        } else {
+            last[self.pos] = 0x80;
+            &self.k2
+        };
+        let mut mac = self.x;
+        mac.iter_mut()
+            .zip(&last)
+            .zip(k)
+            .for_each(|((m, b), k)| *m ^= *b ^ *k);
+        self.block.encrypt_inplace(&mut mac);
+        mac
+    }
+
+    // out may be shorter than the block size for a truncated MAC.
+    pub fn sum_into(&self, out: &mut [u8]) {
+        let mac = self.sum();
+        let n = out.len().min(BLOCK_SIZE);
+        out[..n].copy_from_slice(&mac[..n]);
+    }
+
+    // Compares mac with the MAC in constant time, mac must be the full block.
+    pub fn verify(&self, mac: &[u8]) -> bool {
+        mac.len() == BLOCK_SIZE && bool::from(self.sum().ct_eq(mac))
+    }
+
+    // Compares mac with the leftmost mac.len() bytes of the MAC in constant time.
+    // mac has at least min_len bytes, and min_len is at least CMAC_MIN_TRUNCATED_SIZE.
+    pub fn verify_truncated(&self, mac: &[u8], min_len: usize) -> bool {
+        if mac.len() < min_len.max(CMAC_MIN_TRUNCATED_SIZE) || mac.len() > BLOCK_SIZE {
+            return false;
+        }
+        self.sum()[..mac.len()].ct_eq(mac).into()
+    }
+}
+
+impl<B: Block> Drop for CMac<B> {
+    fn drop(&mut self) {
+        self.k1.fill(0);
+        self.k2.fill(0);
+        self.x.fill(0);
+        self.buf.fill(0);
+    }
+}
+
+// Doubling in GF(2^128), big-endian.
+fn dbl(v: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+    let n = u128::from_be_bytes(*v);
+    let carry = (n >> 127) as u8;
+    let mut out = (n << 1).to_be_bytes();
+    out[BLOCK_SIZE - 1] ^= RB & carry.wrapping_neg();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    const KEY: [u8; 16] = hex!("0123456789ABCDEFFEDCBA9876543210");
+    const MSG: &[u8; 64] = b"AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDD";
+
+    #[test]
+    fn test_cmac() {
+        // openssl mac -cipher SM4-CBC CMAC
+        let tests: [(usize, [u8; 16]); 4] = [
+            (0, hex!("29E154322E5C7BD8EE6A25BA549B24BC")),
+            (16, hex!("55FA750A3DD7B061472EB7F6564D8DB7")),
+            (20, hex!("8A523F5BDAECCCCE207C6FCDFC6FBD24")),
+            (64, hex!("760CA5C76260EFDF410F63117A9076F4")),
+        ];
+        let mut m = new_sm4_cmac(&KEY);
+        for (n, wanted) in tests {
+            assert_eq!(sm4_cmac(&KEY, &MSG[..n]), wanted, "{}", n);
+
+            // any split of the data.
+            for split in 0..=n {
+                m.reset();
+                m.write(&MSG[..split]);
+                m.write(&MSG[split..n]);
+                assert_eq!(m.sum(), wanted, "{} {}", n, split);
+            }
+            assert!(m.verify(&wanted));
+            assert!(!m.verify(&wanted[..4]));
+            assert!(m.verify_truncated(&wanted[..8], 8));
+            assert!(m.verify_truncated(&wanted[..12], 4));
+            let mut mac = [0u8; 8];
+            m.sum_into(&mut mac);
+            assert_eq!(mac, wanted[..8]);
+        }
+    }
+
+    #[test]
+    fn test_cmac_verify() {
+        let mut m = new_sm4_cmac(&KEY);
+        m.write(MSG);
+        let mut mac = m.sum();
+        mac[15] ^= 1;
+        assert!(!m.verify(&mac));
+        assert!(!m.verify(&mac[..15]));
+        assert!(!m.verify(&[]));
+        assert!(!m.verify(&[0; 17]));
+
+        assert!(!m.verify_truncated(&mac, 8));
+        assert!(m.verify_truncated(&mac[..15], 8));
+        assert!(!m.verify_truncated(&mac[..15], 16));
+        // never shorter than 8 bytes.
+        assert!(!m.verify_truncated(&mac[..7], 1));
+        assert!(!m.verify_truncated(&[], 0));
+    }
+}
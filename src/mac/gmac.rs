@@ -0,0 +1,177 @@
+use subtle::ConstantTimeEq;
+
+use crate::blockmode::gcm::GHash;
+use crate::sm4;
+use crate::traits::Block;
+
+const BLOCK_SIZE: usize = 16;
+const STD_NONCE_SIZE: usize = 12;
+// The tag sizes accepted are those of GCM, see gcm::check_sizes.
+const MIN_TAG_SIZE: usize = 12;
+
+// GMac is the GMAC of NIST SP 800-38D, i.e., GCM with an empty plaintext,
+// the data written is the additional data. A nonce must not be reused with
+// the same key.
+pub struct GMac<B: Block> {
+    block: B,
+    ghash: GHash,
+    // E(J_0)
+    tag_mask: [u8; BLOCK_SIZE],
+    length: u64,
+}
+
+pub type Sm4Gmac = GMac<sm4::Cipher>;
+
+// The key must have length 16.
+pub fn new_sm4_gmac(key: &[u8], nonce: &[u8]) -> Sm4Gmac {
+    GMac::new(sm4::Cipher::new(&key), nonce)
+}
+
+pub fn sm4_gmac(key: &[u8], nonce: &[u8], data: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut m = new_sm4_gmac(key, nonce);
+    m.write(data);
+    m.sum()
+}
+
+impl<B: Block> GMac<B> {
+    // The nonce is of any non-zero length, 12 is recommended.
+    pub fn new(block: B, nonce: &[u8]) -> Self {
+        assert!(!nonce.is_empty(), "GMAC nonce must not be empty");
+        debug_assert_eq!(block.block_size(), BLOCK_SIZE);
+
+        // H = E(0^128)
+        let mut key = [0u8; BLOCK_SIZE];
+        block.encrypt_inplace(&mut key);
+        let ghash = GHash::new(&key);
+        key.fill(0);
+
+        let mut m = GMac { block, ghash, tag_mask: [0; BLOCK_SIZE], length: 0 };
+        m.set_nonce(nonce);
+        m
+    }
+
+    // Starts a new MAC with the same key and another nonce.
+    pub fn reset(&mut self, nonce: &[u8]) {
+        assert!(!nonce.is_empty(), "GMAC nonce must not be empty");
+        self.ghash.reset();
+        self.set_nonce(nonce);
+    }
+
+    fn set_nonce(&mut self, nonce: &[u8]) {
+        let mut j0 = [0u8; BLOCK_SIZE];
+        if nonce.len() == STD_NONCE_SIZE {
+            j0[..STD_NONCE_SIZE].copy_from_slice(nonce);
+            j0[BLOCK_SIZE - 1] = 1;
+        } else {
+            // J_0 = GHASH(nonce || pad || 0^64 || len(nonce))
+            let mut g = self.ghash.clone();
+            g.update(nonce);
+            g.pad();
+            g.update(&0u64.to_be_bytes());
+            g.update(&(nonce.len() as u64 * 8).to_be_bytes());
+            j0 = g.finalize();
+        }
+        self.block.encrypt_inplace(&mut j0);
+        self.tag_mask = j0;
+        self.length = 0;
+    }
+
+    pub fn write(&mut self, data: &[u8]) {
+        self.ghash.update(data);
+        self.length += data.len() as u64;
+    }
+
+    // Returns the MAC of the data written so far, more data can be written after.
+    pub fn sum(&self) -> [u8; BLOCK_SIZE] {
+        let mut g = self.ghash.clone();
+        g.pad();
+        g.update(&(self.length * 8).to_be_bytes());
+        g.update(&0u64.to_be_bytes());
+        let mut mac = g.finalize();
+        mac.iter_mut().zip(&self.tag_mask).for_each(|(m, k)| *m ^= *k);
+        mac
+    }
+
+    // Compares mac with the leftmost mac.len() bytes of the MAC in constant time,
+    // mac has 12..=16 bytes.
+    pub fn verify(&self, mac: &[u8]) -> bool {
+        self.verify_with(mac, false)
+    }
+
+    // Allows the 4 and 8-byte MACs as well. NIST SP 800-38D, Appendix C limits
+    // the length of the data and the number of the invocations with a key for
+    // such MACs, the caller takes care of that.
+    pub fn verify_with_short_tag(&self, mac: &[u8]) -> bool {
+        self.verify_with(mac, true)
+    }
+
+    fn verify_with(&self, mac: &[u8], allow_short_tag: bool) -> bool {
+        let short = allow_short_tag && (mac.len() == 4 || mac.len() == 8);
+        if !short && !(MIN_TAG_SIZE..=BLOCK_SIZE).contains(&mac.len()) {
+            return false;
+        }
+        self.sum()[..mac.len()].ct_eq(mac).into()
+    }
+}
+
+impl<B: Block> Drop for GMac<B> {
+    fn drop(&mut self) {
+        self.tag_mask.fill(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use crate::blockmode::gcm::new_sm4_gcm_std;
+    use crate::traits::AEAD;
+
+    const KEY: [u8; 16] = hex!("0123456789ABCDEFFEDCBA9876543210");
+    const MSG: &[u8; 64] = b"AAAAAAAAAAAAAAAABBBBBBBBBBBBBBBBCCCCCCCCCCCCCCCCDDDDDDDDDDDDDDDD";
+
+    #[test]
+    fn test_gmac() {
+        // openssl mac -cipher SM4-GCM GMAC
+        let nonce12 = hex!("00001234567800000000ABCD");
+        let nonce17 = hex!("000102030405060708090A0B0C0D0E0F10");
+        let tests: [(&[u8], usize, [u8; 16]); 6] = [
+            (&nonce12, 0, hex!("54F157AF32744BB83BBE8AA6F1578B71")),
+            (&nonce12, 20, hex!("7F34492EF809CCCB53BCCA368ED62623")),
+            (&nonce12, 64, hex!("6357338192B5A3D18E0DD05F06EDF451")),
+            (&nonce17, 0, hex!("34FE8F70D4DA29D42C52AC578D11817A")),
+            (&nonce17, 20, hex!("1F3B91F11EA7AEA74450ECC7F2902C28")),
+            (&nonce17, 64, hex!("0358EB5E741BC1BD99E1F6AE7AABFE5A")),
+        ];
+        let mut m = new_sm4_gmac(&KEY, &nonce12);
+        for (nonce, n, wanted) in tests {
+            assert_eq!(sm4_gmac(&KEY, nonce, &MSG[..n]), wanted);
+
+            // any split of the data.
+            for split in 0..=n {
+                m.reset(nonce);
+                m.write(&MSG[..split]);
+                m.write(&MSG[split..n]);
+                assert_eq!(m.sum(), wanted, "{} {}", n, split);
+            }
+            assert!(m.verify(&wanted));
+            assert!(m.verify(&wanted[..12]));
+            assert!(!m.verify(&[]));
+            for n in [1, 4, 8, 11] {
+                assert!(!m.verify(&wanted[..n]), "{}", n);
+            }
+            for n in [4, 8, 12, 16] {
+                assert!(m.verify_with_short_tag(&wanted[..n]), "{}", n);
+            }
+            for n in [0, 1, 6, 11] {
+                assert!(!m.verify_with_short_tag(&wanted[..n]), "{}", n);
+            }
+            assert!(!m.verify_with_short_tag(&[0; 17]));
+        }
+
+        // the tag of GCM with an empty plaintext.
+        let mut tag = [0; 16];
+        new_sm4_gcm_std(&KEY).seal_inplace(&mut [], &mut tag, &nonce12, Some(&MSG[..20])).unwrap();
+        assert_eq!(tag, tests[1].2);
+    }
+}
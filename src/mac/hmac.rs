@@ -2,11 +2,14 @@ use crate::traits::Hash;
 use crate::sm3;
 
 const MAX_BLOCK_SIZE: usize = 64;
+
+// The inner and the outer have absorbed the ipad and the opad, a clone is an
+// HMac keyed already, e.g., for the PRF of PBKDF2.
+#[derive(Clone)]
 pub struct HMac<H: Hash<DIGEST_SIZE>, const DIGEST_SIZE:usize>
 {
     outer: H,
     inner: H,
-    opad: [u8; MAX_BLOCK_SIZE], // only opad[..block_size] are available.
 
     // for reset.
     block_size: usize,
@@ -27,43 +30,36 @@ impl<H: Hash<DIGEST_SIZE>,const DIGEST_SIZE:usize> HMac<H, DIGEST_SIZE>{
     pub fn reset(&mut self){
         let block_size = self.block_size;
         let mut ipad = [0u8; MAX_BLOCK_SIZE];
+        let mut opad = [0u8; MAX_BLOCK_SIZE];
         
         for i in 0..block_size {
             ipad[i] = self.processed_key[i] ^ 0x36;
-            self.opad[i] = self.processed_key[i] ^ 0x5c;
+            opad[i] = self.processed_key[i] ^ 0x5c;
         }
         self.inner.reset();
         self.outer.reset();
         self.inner.write(&ipad[..block_size]);
+        self.outer.write(&opad[..block_size]);
     }
 
-    fn new_f(key: &[u8], f: fn()->H) -> Self{
+    pub(crate) fn new_f(key: &[u8], f: fn()->H) -> Self{
         let mut h = HMac {
             block_size: 0,
             outer: f(),
             inner: f(),
-            opad: [0u8; MAX_BLOCK_SIZE],
             processed_key:[0u8; MAX_BLOCK_SIZE],
         };
         let block_size = h.inner.block_size();
         debug_assert!(block_size <= MAX_BLOCK_SIZE);
         h.block_size = block_size;
 
-        let mut ipad = [0u8; MAX_BLOCK_SIZE];
-        let processed_key = &mut h.processed_key;
-
         if key.len() > block_size {
-            processed_key[..DIGEST_SIZE].copy_from_slice(&sum::<H, DIGEST_SIZE>(&f, key));
+            h.processed_key[..DIGEST_SIZE].copy_from_slice(&sum::<H, DIGEST_SIZE>(&f, key));
         } else {
-            processed_key[..key.len()].copy_from_slice(key);
+            h.processed_key[..key.len()].copy_from_slice(key);
         }
 
-        for i in 0..block_size {
-            ipad[i] = processed_key[i] ^ 0x36;
-            h.opad[i] = processed_key[i] ^ 0x5c;
-        }
-
-        h.inner.write(&ipad[..block_size]);
+        h.reset();
         h
     }
 
@@ -72,11 +68,7 @@ impl<H: Hash<DIGEST_SIZE>,const DIGEST_SIZE:usize> HMac<H, DIGEST_SIZE>{
     }
 
     pub fn sum(&mut self) -> [u8; DIGEST_SIZE] {
-        let block_size = self.inner.block_size();
-        let opad = &mut self.opad[0..block_size];
-
         let mut mac = self.inner.sum();
-        self.outer.write(opad);
         self.outer.write(&mac);
         self.outer.sum_into(&mut mac);
         mac
@@ -84,7 +76,6 @@ impl<H: Hash<DIGEST_SIZE>,const DIGEST_SIZE:usize> HMac<H, DIGEST_SIZE>{
 
     pub fn sum_into(&mut self, out: &mut [u8]) {
         let mac = self.inner.sum();
-        self.outer.write(&self.opad);
         self.outer.write(&mac);
         self.outer.sum_into(out);
     }
@@ -1,3 +1,9 @@
+pub mod cbcmac;
+pub mod cmac;
+pub mod gmac;
 #[macro_use]
 pub mod hmac;
+pub use cbcmac::*;
+pub use cmac::*;
+pub use gmac::*;
 pub use hmac::*;
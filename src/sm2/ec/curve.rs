@@ -40,6 +40,13 @@ impl AffinePoint {
         infinity: false,
     };
 
+    // The curve parameter b in montgomery representation, a = -3.
+    const B: GFpElement = GFpElement {
+        limbs: [0x90d230632bc0dd42, 0x71cf379ae9b537ab, 0x527981505ea51c3c, 0x240fe188ba20e2c8],
+    };
+
+    /// Creates a point from coordinates which are not checked, use try_new
+    /// for untrusted inputs.
     pub fn new(x: U256, y: U256)-> AffinePoint {
         AffinePoint{
             x: GFpElement::from(x),
@@ -48,6 +55,49 @@ impl AffinePoint {
         }
     }
 
+    /// Creates a point from untrusted coordinates, returns None if x or y
+    /// is not in [0, p), or (x, y) is not on the curve.
+    pub fn try_new(x: U256, y: U256) -> Option<AffinePoint> {
+        if !GFpElement::is_reduced(&x) || !GFpElement::is_reduced(&y) {
+            return None;
+        }
+        let p = AffinePoint::new(x, y);
+        if !p.is_on_curve() {
+            return None;
+        }
+        Some(p)
+    }
+
+    /// Recovers the point from x and the parity of y, returns None if x is not
+    /// in [0, p) or x^3 - 3x + b is not a square.
+    pub fn decompress(x: U256, y_odd: bool) -> Option<AffinePoint> {
+        if !GFpElement::is_reduced(&x) {
+            return None;
+        }
+        let xm = GFpElement::from(x);
+        let x3 = GFpElement::new_from_square(&xm).mul_move(&xm);
+        let three_x = GFpElement::new_from_double(&xm).add_move(&xm);
+        let mut y = x3.sub_move(&three_x).add_move(&Self::B).sqrt()?;
+        // The curve has a prime order, there is no point with y = 0,
+        // so exactly one of y and p - y has the wanted parity.
+        if (U256::from(y).v[0] & 1 == 1) != y_odd {
+            y.neg();
+        }
+        Some(AffinePoint { x: xm, y, infinity: false })
+    }
+
+    /// Returns if y^2 = x^3 - 3x + b, the infinity is not on the curve.
+    pub fn is_on_curve(&self) -> bool {
+        if self.infinity {
+            return false;
+        }
+        let x3 = GFpElement::new_from_square(&self.x).mul_move(&self.x);
+        let three_x = GFpElement::new_from_double(&self.x).add_move(&self.x);
+        let rhs = x3.sub_move(&three_x).add_move(&Self::B);
+        let lhs = GFpElement::new_from_square(&self.y);
+        lhs == rhs
+    }
+
     pub fn new_from_scalar_base_mul(scalar: &[u64;4])-> AffinePoint{
         JacobianPoint::new_from_scalar_base_mul(scalar).into()
     }
@@ -106,16 +156,6 @@ impl From<JacobianPoint> for AffinePoint {
     }
 }
 
-impl From<(U256, U256)> for AffinePoint {
-    fn from(value: (U256, U256)) -> Self {
-        AffinePoint { 
-            x: value.0.into(), 
-            y: value.1.into(), 
-            infinity: false,
-        }
-    }
-}
-
 
 #[derive(Clone, Copy, Debug)]
 pub struct JacobianPoint {
@@ -141,15 +181,7 @@ impl From<AffinePoint> for JacobianPoint {
         JacobianPoint::from_affine(&value)
     }
 }
-impl From<(U256, U256)> for JacobianPoint {
-    fn from(value: (U256, U256)) -> Self {
-        JacobianPoint { 
-            x: value.0.into(), 
-            y: value.1.into(), 
-            z: GFpElement::R,
-        }
-    }
-}
+
 
 
 
@@ -374,6 +406,11 @@ impl JacobianPoint {
         }
     }
 
+    /// Creates a point from untrusted affine coordinates, see AffinePoint::try_new.
+    pub fn try_new(x: U256, y: U256) -> Option<JacobianPoint> {
+        AffinePoint::try_new(x, y).map(|p| JacobianPoint::from_affine(&p))
+    }
+
     /// Normalize the points to z = 1, or (0,0,0) if z = 0.
     ///
     /// Note that instead (0,1,0), we returns (0,0,0) when z = 0 (to void the if z.is_zero()
@@ -708,6 +745,31 @@ mod tests {
     use super::AffinePoint;
     use super::GFpElement;
     use super::JacobianPoint;
+    use crate::sm2::U256;
+
+    #[test]
+    fn test_try_new() {
+        let gx = U256 { v: [0x715A4589334C74C7, 0x8FE30BBFF2660BE1, 0x5F9904466A39C994, 0x32C4AE2C1F198119] };
+        let gy = U256 { v: [0x02DF32E52139F0A0, 0xD0A9877CC62A4740, 0x59BDCEE36B692153, 0xBC3736A2F4F6779C] };
+        let g = AffinePoint::try_new(gx, gy).unwrap();
+        assert_eq!(g, AffinePoint::BASE);
+        assert!(AffinePoint::BASE.is_on_curve());
+        assert!(!AffinePoint::INFINITY.is_on_curve());
+
+        let mut p = AffinePoint::new_from_scalar_base_mul(&rand::rng().random());
+        assert!(p.is_on_curve());
+        p.y.add(&GFpElement::R);
+        assert!(!p.is_on_curve());
+
+        assert!(AffinePoint::try_new(U256::default(), U256::default()).is_none());
+        assert!(AffinePoint::try_new(gx, U256 { v: [gy.v[0] ^ 1, gy.v[1], gy.v[2], gy.v[3]] }).is_none());
+
+        // x + p is congruent to a valid x but out of range.
+        let prime = U256 { v: GFpElement::PRIME.limbs };
+        assert!(GFpElement::is_reduced(&gx));
+        assert!(!GFpElement::is_reduced(&prime));
+        assert!(AffinePoint::try_new(prime, gy).is_none());
+    }
 
     fn get_test_points() -> (JacobianPoint, JacobianPoint, JacobianPoint) {
         // G - affine
@@ -27,6 +27,12 @@ const R1: u64 = 0x8dfc2094de39fad4;
 const R2: u64 = 0;
 const R3: u64 = 0x100000000;
 
+// 2^512 % N
+const RR0: u64 = 0x901192af7c114f20;
+const RR1: u64 = 0x3464504ade6fa2fa;
+const RR2: u64 = 0x620fc84c3affe0d4;
+const RR3: u64 = 0x1eb5e412a22b3d3b;
+
 impl From<&[u8;32]> for GFnElement {
     fn from(v: &[u8;32]) -> Self {
         let (chunks,_) = v.as_chunks::<8>();
@@ -125,7 +131,26 @@ impl GFnElement {
 
     // 2^256 % n
     pub const R: GFnElement = GFnElement { limbs: [R0, R1, R2, R3] };
+
+    // 2^512 % n, multiply by RR transforms a into the montgomery form a*R.
+    pub const RR: GFnElement = GFnElement { limbs: [RR0, RR1, RR2, RR3] };
     pub const ONE: GFnElement = GFnElement { limbs: [1,0,0,0]};
+
+    /// Returns if d is in [1, n-2], the range of a SM2 private key,
+    /// for 1 + d must be invertible mod n.
+    pub fn is_valid_private_key(d: &U256) -> bool {
+        let d = &d.v;
+        let (_, _, _, _, borrow) = sub256(d[0], d[1], d[2], d[3], N0 - 1, N1, N2, N3);
+        borrow & !constant_eq256(d, &[0; 4])
+    }
+
+    /// Returns if v is in [1, n-1], the range of r and s of a SM2 signature.
+    pub fn is_valid_scalar(v: &U256) -> bool {
+        let v = &v.v;
+        let (_, _, _, _, borrow) = sub256(v[0], v[1], v[2], v[3], N0, N1, N2, N3);
+        borrow & !constant_eq256(v, &[0; 4])
+    }
+
     #[inline]
     pub fn copy_from(&mut self, b: &GFnElement) -> &mut Self {
         *self = *b;
@@ -262,6 +287,11 @@ impl GFnElement {
         self
     }
 
+    #[inline]
+    pub fn transform_to_mont(&mut self) -> &mut Self {
+        self.mul(&GFnElement::RR)
+    }
+
     #[inline]
     pub fn mul_one(&mut self) -> &mut Self {
         let a0 = self.limbs[0];
@@ -489,6 +519,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_mont() {
+        let rinv = to_bigint(&GFnElement::R.limbs).modinv(&get_module()).unwrap();
+        let rr = to_bigint(&GFnElement::RR.limbs);
+        assert_eq!(rr * &rinv % get_module(), to_bigint(&GFnElement::R.limbs));
+        for _ in 0..1000 {
+            let a = random();
+            let mut b = a;
+            b.transform_to_mont().mul_one();
+            assert_eq!(a, b);
+        }
+    }
+
     #[test]
     fn test_invert_speed() {
         // 132k
@@ -131,6 +131,33 @@ impl GFpElement {
         out
     }
 
+    /// Returns the square root of self if self is a quadratic residue.
+    /// p = 3 mod 4, thus sqrt(a) = a^((p+1)/4), the exponent is public,
+    /// a square-and-multiply is good enough.
+    pub fn sqrt(&self) -> Option<GFpElement> {
+        const EXP: [LIMB; 4] = [0x4000000000000000, 0xffffffffc0000000, 0xffffffffffffffff, 0x3fffffffbfffffff];
+        let mut r = GFpElement::R;
+        for i in (0..256).rev() {
+            r.square();
+            if (EXP[i / 64] >> (i % 64)) & 1 == 1 {
+                r.mul(self);
+            }
+        }
+        if GFpElement::new_from_square(&r) == *self {
+            Some(r)
+        } else {
+            None
+        }
+    }
+
+    /// Returns if v < p, i.e., v is a canonical field element.
+    #[inline]
+    pub fn is_reduced(v: &U256) -> bool {
+        let p = &Self::PRIME.limbs;
+        let (_, _, _, _, borrow) = sub256(v.v[0], v.v[1], v.v[2], v.v[3], p[0], p[1], p[2], p[3]);
+        borrow
+    }
+
     /// Returns if self is zero.
     #[inline]
     pub fn is_zero(&self) -> bool {
@@ -660,6 +687,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_sqrt() {
+        for _ in 0..1000 {
+            let a = random();
+            let sq = GFpElement::new_from_square(&a);
+            let r = sq.sqrt().unwrap();
+            assert!(r == a || r == a.neg_move());
+        }
+        assert_eq!(GFpElement::ZERO.sqrt(), Some(GFpElement::ZERO));
+        // -1 is not a quadratic residue for p = 3 mod 4.
+        assert!(GFpElement::R.neg_move().sqrt().is_none());
+    }
+
     #[test]
     fn test_invert() {
         let mut c = GFpElement::default();
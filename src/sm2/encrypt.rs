@@ -2,7 +2,9 @@ use super::{
     ec::curve::AffinePoint,
     PrivateKey, PublicKey, U256,
 };
+use crate::kdf::Sm3Kdf;
 use crate::sm3;
+use alloc::vec::Vec;
 use subtle::ConstantTimeEq;
 use crate::sm2::error::{SM2Error, Result};
 
@@ -18,119 +20,114 @@ pub struct Cipher<const N:usize> {
     pub c: [u8; N],
 }
 
-/// encrypt computes the cipher
+/// Ciphertext is the SM2 cipher whose C2 length is known only at runtime,
+/// the C2 is allocated on the heap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ciphertext {
+    pub x: U256,
+    pub y: U256,
+    pub h: [u8; sm3::DIGEST_SIZE],
+    pub c: Vec<u8>,
+}
+
+impl<const N: usize> From<Cipher<N>> for Ciphertext {
+    fn from(cipher: Cipher<N>) -> Self {
+        Ciphertext {
+            x: cipher.x,
+            y: cipher.y,
+            h: cipher.h,
+            c: cipher.c.to_vec(),
+        }
+    }
+}
+
+/// encrypt computes the cipher, returns InvalidPoint if pk is not on the curve,
+/// or InvalidKeyLength if data is longer than the KDF output.
 /// The N can not too big, or stack overflows.
-pub fn encrypt<const N:usize>(pk: &PublicKey, data: &[u8;N], k: &[u64; 4]) -> Cipher<N> {
+pub fn encrypt<const N:usize>(pk: &PublicKey, data: &[u8;N], k: &[u64; 4]) -> Result<Cipher<N>> {
+    let mut c = [0_u8; N];
+    let (x, y, h) = encrypt_inner(pk, data, k, &mut c)?;
+    Ok(Cipher { x, y, h, c })
+}
+
+pub fn decrypt<const N:usize>(sk: &PrivateKey, cipher: &Cipher<N>) -> Result<[u8;N]> {
+    let mut m = [0_u8; N];
+    decrypt_inner(sk, &cipher.x, &cipher.y, &cipher.h, &cipher.c, &mut m)?;
+    Ok(m)
+}
+
+/// encrypt_vec computes the cipher of a plaintext with any length.
+pub fn encrypt_vec(pk: &PublicKey, data: &[u8], k: &[u64; 4]) -> Result<Ciphertext> {
+    let mut c = vec![0_u8; data.len()];
+    let (x, y, h) = encrypt_inner(pk, data, k, &mut c)?;
+    Ok(Ciphertext { x, y, h, c })
+}
+
+pub fn decrypt_vec(sk: &PrivateKey, cipher: &Ciphertext) -> Result<Vec<u8>> {
+    let mut m = vec![0_u8; cipher.c.len()];
+    decrypt_inner(sk, &cipher.x, &cipher.y, &cipher.h, &cipher.c, &mut m)?;
+    Ok(m)
+}
+
+// Computes C2 into c, which has the same length as data, returns (C1, C3).
+fn encrypt_inner(pk: &PublicKey, data: &[u8], k: &[u64; 4], c: &mut [u8]) -> Result<(U256, U256, [u8; sm3::DIGEST_SIZE])> {
+    // a pk off the curve leaks k through [k]pk.
+    let mut s = AffinePoint::try_new(pk.x, pk.y).ok_or(SM2Error::InvalidPoint)?;
     let c1 = AffinePoint::new_from_scalar_base_mul(k);
-    let mut s = AffinePoint::from((pk.x, pk.y));
     s.scalar_mul(k);
     let x = U256::from(s.x);
     let y = U256::from(s.y);
 
-    let mut kdf = KDF::new();
-    kdf.read(x.v[3].to_be_bytes().as_slice());
-    kdf.read(x.v[2].to_be_bytes().as_slice());
-    kdf.read(x.v[1].to_be_bytes().as_slice());
-    kdf.read(x.v[0].to_be_bytes().as_slice());
-    kdf.read(y.v[3].to_be_bytes().as_slice());
-    kdf.read(y.v[2].to_be_bytes().as_slice());
-    kdf.read(y.v[1].to_be_bytes().as_slice());
-    kdf.read(y.v[0].to_be_bytes().as_slice());
-    let mut c = [0_u8; N];
-    kdf.write(c.as_mut());
-    for i in 0..N {
+    kdf_xy(&x, &y, c)?;
+    for i in 0..c.len() {
         c[i] ^= data[i];
     }
-    let mut c3_hash = sm3::Digest::new();
-    c3_hash.write(x.v[3].to_be_bytes().as_slice());
-    c3_hash.write(x.v[2].to_be_bytes().as_slice());
-    c3_hash.write(x.v[1].to_be_bytes().as_slice());
-    c3_hash.write(x.v[0].to_be_bytes().as_slice());
-    c3_hash.write(data);
-    c3_hash.write(y.v[3].to_be_bytes().as_slice());
-    c3_hash.write(y.v[2].to_be_bytes().as_slice());
-    c3_hash.write(y.v[1].to_be_bytes().as_slice());
-    c3_hash.write(y.v[0].to_be_bytes().as_slice());
-
-    Cipher {
-        x: U256::from(c1.x),
-        y: U256::from(c1.y),
-        h: c3_hash.sum(),
-        c: c,
-    }
+    Ok((U256::from(c1.x), U256::from(c1.y), c3_hash(&x, data, &y)))
 }
 
-pub fn decrypt<const N:usize>(sk: &PrivateKey, cipher: &Cipher<N>) -> Result<[u8;N]> {
-    let mut s = AffinePoint::new(cipher.x, cipher.y);
+// Recovers the plaintext into m, which has the same length as c.
+fn decrypt_inner(sk: &PrivateKey, x: &U256, y: &U256, h: &[u8; sm3::DIGEST_SIZE], c: &[u8], m: &mut [u8]) -> Result<()> {
+    // C1 is controlled by the sender, reject it before touching the private key.
+    let mut s = AffinePoint::try_new(*x, *y).ok_or(SM2Error::InvalidPoint)?;
     s.scalar_mul(&sk.d.v);
+    if s.infinity {
+        return Err(SM2Error::InvalidPoint);
+    }
     let x = U256::from(s.x);
     let y = U256::from(s.y);
 
-    let mut kdf = KDF::new();
-    kdf.read(x.v[3].to_be_bytes().as_slice());
-    kdf.read(x.v[2].to_be_bytes().as_slice());
-    kdf.read(x.v[1].to_be_bytes().as_slice());
-    kdf.read(x.v[0].to_be_bytes().as_slice());
-    kdf.read(y.v[3].to_be_bytes().as_slice());
-    kdf.read(y.v[2].to_be_bytes().as_slice());
-    kdf.read(y.v[1].to_be_bytes().as_slice());
-    kdf.read(y.v[0].to_be_bytes().as_slice());
-    let mut m = [0_u8; N];
-    kdf.write(m.as_mut());
+    kdf_xy(&x, &y, m)?;
     for i in 0..m.len() {
-        m[i] ^= cipher.c[i];
+        m[i] ^= c[i];
     }
-    let mut c3_hash = sm3::Digest::new();
-    c3_hash.write(x.v[3].to_be_bytes().as_slice());
-    c3_hash.write(x.v[2].to_be_bytes().as_slice());
-    c3_hash.write(x.v[1].to_be_bytes().as_slice());
-    c3_hash.write(x.v[0].to_be_bytes().as_slice());
-    c3_hash.write(m.as_slice());
-    c3_hash.write(y.v[3].to_be_bytes().as_slice());
-    c3_hash.write(y.v[2].to_be_bytes().as_slice());
-    c3_hash.write(y.v[1].to_be_bytes().as_slice());
-    c3_hash.write(y.v[0].to_be_bytes().as_slice());
-    let hash = c3_hash.sum();
-    let eq: bool = hash.ct_eq(cipher.h.as_slice()).into();
+    let hash = c3_hash(&x, m, &y);
+    let eq: bool = hash.ct_eq(h.as_slice()).into();
     if !eq {
         Err(SM2Error::InvalidCipherHash)
     } else {
-        Ok(m)
+        Ok(())
     }
 }
-// We need copy the internal states for different counter.
-#[derive(Debug, Clone, Copy)]
-struct KDF {
-    hash: sm3::Digest,
-}
-
-impl KDF {
-    fn new() -> KDF {
-        KDF { hash: sm3::Digest::new() }
-    }
 
-    // KDF read multiple times from input.
-    fn read(&mut self, input: &[u8]) -> &mut Self {
-        self.hash.write(input);
-        self
-    }
+// t = KDF(x2 || y2, klen) into out, klen is at most (2^32 - 1) * 32 bytes.
+#[inline]
+fn kdf_xy(x: &U256, y: &U256, out: &mut [u8]) -> Result<()> {
+    Sm3Kdf::new()
+        .write(x.to_be_bytes().as_slice())
+        .write(y.to_be_bytes().as_slice())
+        .reader()
+        .read(out)
+        .map_err(|_| SM2Error::InvalidKeyLength(out.len()))
+}
 
-    // KDF can only write once!
-    fn write(mut self, output: &mut [u8]) {
-        let mut ct = 1_u32;
-        const CHUNK_SIZE: usize = sm3::DIGEST_SIZE;
-        let (chunks, tail) = output.as_chunks_mut::<CHUNK_SIZE>();
-        for chunk in chunks {
-            let mut hash = self.hash;
-            hash.write(ct.to_be_bytes().as_slice());
-            hash.sum_into(chunk);
-            ct += 1;
-        }
-        let tail_hash = self.hash.write(ct.to_be_bytes().as_slice()).sum();
-        for i in 0..tail.len() {
-            tail[i] = tail_hash[i];
-        }
-    }
+// C3 = Hash(x2 || M || y2)
+#[inline]
+fn c3_hash(x: &U256, m: &[u8], y: &U256) -> [u8; sm3::DIGEST_SIZE] {
+    let mut c3_hash = sm3::Digest::new();
+    c3_hash.write(x.to_be_bytes().as_slice());
+    c3_hash.write(m);
+    c3_hash.write(y.to_be_bytes().as_slice());
+    c3_hash.sum()
 }
 
 #[cfg(test)]
@@ -147,10 +144,70 @@ mod tests {
         const N:usize = 32 * 1024;
         let m = [1u8; N];
         let k = rng.random();
-        let cipher = encrypt(&pk, &m, &k);
+        let cipher = encrypt(&pk, &m, &k).unwrap();
 
         let mm = decrypt(&sk, &cipher).unwrap();
 
         assert_eq!(m, mm.as_slice());
     }
+
+    #[test]
+    fn test_enc_vec() {
+        let mut rng = rand::rng();
+        let sk = PrivateKey::new(&mut rng);
+        let pk = sk.public();
+
+        for n in [0, 1, 16, 31, 32, 33, 48, 1000, 100 * 1024] {
+            let m: Vec<u8> = (0..n).map(|i| i as u8).collect();
+            let k = rng.random();
+            let cipher = encrypt_vec(&pk, &m, &k).unwrap();
+            assert_eq!(cipher.c.len(), n);
+            assert_eq!(decrypt_vec(&sk, &cipher).unwrap(), m);
+
+            let mut bad = cipher.clone();
+            bad.h[0] ^= 1;
+            assert!(decrypt_vec(&sk, &bad).is_err());
+        }
+    }
+
+    #[test]
+    fn test_dec_invalid_point() {
+        let mut rng = rand::rng();
+        let sk = PrivateKey::new(&mut rng);
+        let pk = sk.public();
+
+        let mut cipher = encrypt_vec(&pk, &[1u8; 16], &rng.random()).unwrap();
+        cipher.y.v[0] ^= 1;
+        assert_eq!(decrypt_vec(&sk, &cipher), Err(SM2Error::InvalidPoint));
+        cipher.x = U256::default();
+        cipher.y = U256::default();
+        assert_eq!(decrypt_vec(&sk, &cipher), Err(SM2Error::InvalidPoint));
+    }
+
+    #[test]
+    fn test_enc_vec_compatible() {
+        let mut rng = rand::rng();
+        let sk = PrivateKey::new(&mut rng);
+        let pk = sk.public();
+
+        let m = [7u8; 48];
+        let k = rng.random();
+        let cipher = Ciphertext::from(encrypt(&pk, &m, &k).unwrap());
+        assert_eq!(cipher, encrypt_vec(&pk, &m, &k).unwrap());
+        assert_eq!(decrypt_vec(&sk, &cipher).unwrap(), m);
+    }
+
+    #[test]
+    fn test_enc_invalid_point() {
+        let mut rng = rand::rng();
+        let mut pk = PrivateKey::new(&mut rng).public();
+        let k = rng.random();
+
+        pk.y.v[0] ^= 1;
+        assert!(matches!(encrypt(&pk, &[1u8; 16], &k), Err(SM2Error::InvalidPoint)));
+        assert_eq!(encrypt_vec(&pk, &[1u8; 16], &k), Err(SM2Error::InvalidPoint));
+        pk.x = U256::default();
+        pk.y = U256::default();
+        assert_eq!(encrypt_vec(&pk, &[1u8; 16], &k), Err(SM2Error::InvalidPoint));
+    }
 }
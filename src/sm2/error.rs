@@ -1,12 +1,27 @@
 use thiserror;
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
 pub enum SM2Error {
     #[error("invalid point")]
     InvalidPoint,
 
+    #[error("unsupported point format: {:#04x}", .0)]
+    UnsupportedPointFormat(u8),
+
+    #[error("invalid point encoding length, want {}, got {}", .0, .1)]
+    InvalidPointLength(usize, usize),
+
+    #[error("invalid private key, must be in [1, n-2]")]
+    InvalidPrivateKey,
+
     #[error("sm2 cipher hash check failed")]
     InvalidCipherHash,
+
+    #[error("sm2 key exchange confirmation failed")]
+    KeyConfirmationFailed,
+
+    #[error("invalid key length {}", .0)]
+    InvalidKeyLength(usize),
     
     #[error("unknown error")]
     Unknown,
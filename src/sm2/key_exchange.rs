@@ -1,16 +1,330 @@
-use super::U256;
+// SM2 key exchange protocol, see GB/T 32918.3-2016.
+//
+// The initiator A and the responder B each hold a static key pair (dA, PA), (dB, PB)
+// and the Z value of their identities (ZA, ZB). Both sides generate an ephemeral key
+// r, R = [r]G and exchange R. Then:
+//
+//   x' = 2^w + (x & (2^w - 1)), w = 127
+//   A:  tA = (dA + x1'·rA) mod n,  U = [tA](PB + [x2']RB)
+//   B:  tB = (dB + x2'·rB) mod n,  V = [tB](PA + [x1']RA)
+//   K  = KDF(xU || yU || ZA || ZB, klen)
+//
+// where (x1, y1) = RA and (x2, y2) = RB. U = V, thus both sides share the same key.
+// The optional key confirmation uses
+//
+//   S1 = SB = Hash(0x02 || yU || Hash(xU || ZA || ZB || x1 || y1 || x2 || y2))
+//   S2 = SA = Hash(0x03 || yU || Hash(xU || ZA || ZB || x1 || y1 || x2 || y2))
+//
+// B sends SB to A along with RB, A sends SA back to B.
+//
+// Example:
+//
+//   let a = KeyExchange::new(KeyExchangeRole::Initiator, &sk_a, None, &pk_b, None, &mut rng)?;
+//   let b = KeyExchange::new(KeyExchangeRole::Responder, &sk_b, None, &pk_a, None, &mut rng)?;
+//   let ra = a.ephemeral_public_key().clone();                  // A -> B: RA
+//   let rb = b.ephemeral_public_key().clone();
+//   let kb = b.compute_shared_key(&ra, 16)?;                    // B -> A: RB, SB
+//   let ka = a.compute_shared_key(&rb, 16)?;
+//   ka.verify_peer_confirmation(kb.confirmation())?;            // A -> B: SA
+//   kb.verify_peer_confirmation(ka.confirmation())?;
 
+use alloc::vec::Vec;
+use rand::Rng;
+use subtle::ConstantTimeEq;
 
+use super::ec::curve::{AffinePoint, JacobianPoint};
+use super::ec::gfn::GFnElement;
+use super::error::{Result, SM2Error};
+use super::{precompute_with_id_public_key, PrivateKey, PublicKey, U256};
+use crate::kdf::Sm3Kdf;
+use crate::sm3;
 
-fn generate_agreement_data(_ra: &U256) -> (U256, U256) {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyExchangeRole {
+    // The A side, who sends the first ephemeral public key.
+    Initiator,
+    // The B side.
+    Responder,
+}
+
+pub struct KeyExchange<'a> {
+    role: KeyExchangeRole,
+    private_key: &'a PrivateKey,
+    public_key: PublicKey,
+    z: [u8; 32],
+    peer_public_key: PublicKey,
+    peer_z: [u8; 32],
+
+    // the ephemeral key pair (r, R = [r]G).
+    r: U256,
+    ephemeral: PublicKey,
+}
+
+impl Drop for KeyExchange<'_> {
+    fn drop(&mut self) {
+        self.r.v = [0, 0, 0, 0];
+    }
+}
+
+// The result of a key exchange.
+pub struct SharedKey {
+    pub key: Vec<u8>,
+    // S_B for the responder and S_A for the initiator, sends to the peer.
+    confirmation: [u8; sm3::DIGEST_SIZE],
+    // S_A for the responder and S_B for the initiator, expected from the peer.
+    peer_confirmation: [u8; sm3::DIGEST_SIZE],
+}
+
+impl Drop for SharedKey {
+    fn drop(&mut self) {
+        for x in self.key.iter_mut() {
+            *x = 0;
+        }
+    }
+}
+
+impl SharedKey {
+    // The confirmation value to send to the peer.
+    pub fn confirmation(&self) -> &[u8; sm3::DIGEST_SIZE] {
+        &self.confirmation
+    }
+
+    // Checks the confirmation value received from the peer.
+    pub fn verify_peer_confirmation(&self, s: &[u8]) -> Result<()> {
+        if s.len() != sm3::DIGEST_SIZE {
+            return Err(SM2Error::KeyConfirmationFailed);
+        }
+        let eq: bool = self.peer_confirmation.ct_eq(s).into();
+        match eq {
+            true => Ok(()),
+            false => Err(SM2Error::KeyConfirmationFailed),
+        }
+    }
+}
+
+impl<'a> KeyExchange<'a> {
+    // Starts a key exchange, generating the ephemeral key.
+    // The id and peer_id are the user identities, None for the default id "1234567812345678".
+    pub fn new<R: Rng>(
+        role: KeyExchangeRole,
+        private_key: &'a PrivateKey,
+        id: Option<&[u8]>,
+        peer_public_key: &PublicKey,
+        peer_id: Option<&[u8]>,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let r = generate_ephemeral_scalar(rng);
+        Self::new_with_ephemeral(role, private_key, id, peer_public_key, peer_id, r)
+    }
+
+    fn new_with_ephemeral(
+        role: KeyExchangeRole,
+        private_key: &'a PrivateKey,
+        id: Option<&[u8]>,
+        peer_public_key: &PublicKey,
+        peer_id: Option<&[u8]>,
+        r: U256,
+    ) -> Result<Self> {
+        if !peer_public_key.is_valid() {
+            return Err(SM2Error::InvalidPoint);
+        }
+        let public_key = private_key.public();
+        let z = precompute_with_id_public_key(id, &public_key);
+        let peer_z = precompute_with_id_public_key(peer_id, peer_public_key);
+        let ephemeral = generate_agreement_data(&r);
+        Ok(KeyExchange {
+            role,
+            private_key,
+            public_key,
+            z,
+            peer_public_key: peer_public_key.clone(),
+            peer_z,
+            r,
+            ephemeral,
+        })
+    }
+
+    pub fn role(&self) -> KeyExchangeRole {
+        self.role
+    }
+
+    // Returns R = [r]G, which should be sent to the peer.
+    pub fn ephemeral_public_key(&self) -> &PublicKey {
+        &self.ephemeral
+    }
+
+    // Computes the shared key of key_len bytes and the confirmation values from the
+    // peer's ephemeral public key. The KeyExchange is consumed, for the ephemeral key
+    // must not be reused.
+    pub fn compute_shared_key(self, peer_ephemeral: &PublicKey, key_len: usize) -> Result<SharedKey> {
+        let mut p = JacobianPoint::try_new(peer_ephemeral.x, peer_ephemeral.y).ok_or(SM2Error::InvalidPoint)?;
+
+        // t = (d + x'·r) mod n
+        let mut t = GFnElement::from(x_bar(&self.ephemeral.x));
+        t.transform_to_mont().mul(&GFnElement::from(self.r)).add(&GFnElement::from(self.private_key.d));
 
-    todo!()
+        // U = [t](P + [x']R) of the peer
+        p.scalar_mul(&x_bar(&peer_ephemeral.x).v);
+        let q = JacobianPoint::try_new(self.peer_public_key.x, self.peer_public_key.y).ok_or(SM2Error::InvalidPoint)?;
+        let pp = p;
+        if p.add(&q) {
+            p = pp;
+            p.double();
+        }
+        p.scalar_mul(&t.limbs);
+        let u = AffinePoint::from(p);
+        if u.infinity {
+            return Err(SM2Error::InvalidPoint);
+        }
+        let xu = U256::from(u.x).to_be_bytes();
+        let yu = U256::from(u.y).to_be_bytes();
+
+        // Z_A || Z_B and R_A, R_B
+        let (za, zb, ra, rb) = match self.role {
+            KeyExchangeRole::Initiator => (&self.z, &self.peer_z, &self.ephemeral, peer_ephemeral),
+            KeyExchangeRole::Responder => (&self.peer_z, &self.z, peer_ephemeral, &self.ephemeral),
+        };
+
+        let mut key = vec![0u8; key_len];
+        Sm3Kdf::new()
+            .write(&xu)
+            .write(&yu)
+            .write(za)
+            .write(zb)
+            .reader()
+            .read(&mut key)
+            .map_err(|_| SM2Error::InvalidKeyLength(key_len))?;
+
+        let inner = sm3!(&xu, za, zb, &ra.x.to_be_bytes(), &ra.y.to_be_bytes(), &rb.x.to_be_bytes(), &rb.y.to_be_bytes());
+        let s2 = sm3!(&[2u8], &yu, &inner);
+        let s3 = sm3!(&[3u8], &yu, &inner);
+
+        let (confirmation, peer_confirmation) = match self.role {
+            KeyExchangeRole::Initiator => (s3, s2),
+            KeyExchangeRole::Responder => (s2, s3),
+        };
+        Ok(SharedKey { key, confirmation, peer_confirmation })
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
 }
 
-fn generate_agreement_data_and_key(){
+// Returns a random r in [1, n-1].
+fn generate_ephemeral_scalar<R: Rng>(rng: &mut R) -> U256 {
+    loop {
+        let r = U256::from(GFnElement::from(U256 { v: rng.random() }));
+        if r.v != [0, 0, 0, 0] {
+            return r;
+        }
+    }
+}
+
+// Returns the ephemeral public key R = [r]G.
+fn generate_agreement_data(r: &U256) -> PublicKey {
+    let mut p = AffinePoint::new_from_scalar_base_mul(&r.v);
+    p.x.transform_from_mont();
+    p.y.transform_from_mont();
+    PublicKey { x: U256 { v: p.x.limbs }, y: U256 { v: p.y.limbs } }
+}
 
+// x' = 2^w + (x & (2^w - 1)), w = ceil(ceil(log2(n))/2) - 1 = 127.
+#[inline]
+fn x_bar(x: &U256) -> U256 {
+    U256 { v: [x.v[0], x.v[1] | (1 << 63), 0, 0] }
 }
 
-fn generate_shared_key(){
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::RefCell;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_key_exchange() {
+        let mut rng = rand::rng();
+        let sk_a = PrivateKey::new(&mut rng);
+        let sk_b = PrivateKey::new(&mut rng);
+        let pk_a = sk_a.public();
+        let pk_b = sk_b.public();
+        let id_a = "ALICE123@YAHOO.COM".as_bytes();
+        let id_b = "BILL456@YAHOO.COM".as_bytes();
+
+        for key_len in [0, 16, 32, 48, 100] {
+            let a = KeyExchange::new(KeyExchangeRole::Initiator, &sk_a, Some(id_a), &pk_b, Some(id_b), &mut rng).unwrap();
+            let b = KeyExchange::new(KeyExchangeRole::Responder, &sk_b, Some(id_b), &pk_a, Some(id_a), &mut rng).unwrap();
+            let ra = a.ephemeral_public_key().clone();
+            let rb = b.ephemeral_public_key().clone();
 
-}
\ No newline at end of file
+            let kb = b.compute_shared_key(&ra, key_len).unwrap();
+            let ka = a.compute_shared_key(&rb, key_len).unwrap();
+            assert_eq!(ka.key.len(), key_len);
+            assert_eq!(ka.key, kb.key);
+
+            ka.verify_peer_confirmation(kb.confirmation()).unwrap();
+            kb.verify_peer_confirmation(ka.confirmation()).unwrap();
+            assert_ne!(ka.confirmation(), kb.confirmation());
+        }
+    }
+
+    fn new_private_key(d: [u8; 32]) -> PrivateKey {
+        PrivateKey {
+            d: U256::from_be_slice(&d).unwrap(),
+            d1inv: None,
+            public_key: RefCell::new(None),
+        }
+    }
+
+    // The expected values are computed by an independent implementation.
+    #[test]
+    fn test_key_exchange_vector() {
+        let sk_a = new_private_key(hex!("6FCBA2EF9AE0AB902BC3BDE3FF915D44BA4CC78F88E2F8E7F8996D3B8CCEEDEE"));
+        let sk_b = new_private_key(hex!("5E35D7D3F3C54DBAC72E61819E730B019A84208CA3A35E4C2E353DFCCB2A3B53"));
+        let ra = U256::from_be_slice(&hex!("83A2C9C8B96E5AF70BD480B472409A9A327257F1EBB73F5B073354B248668563")).unwrap();
+        let rb = U256::from_be_slice(&hex!("33FE21940342161C55619C4A0C060293D543C80AF19748CE176D83477DE71C80")).unwrap();
+        let id_a = "ALICE123@YAHOO.COM".as_bytes();
+        let id_b = "BILL456@YAHOO.COM".as_bytes();
+
+        let a = KeyExchange::new_with_ephemeral(KeyExchangeRole::Initiator, &sk_a, Some(id_a), &sk_b.public(), Some(id_b), ra).unwrap();
+        let b = KeyExchange::new_with_ephemeral(KeyExchangeRole::Responder, &sk_b, Some(id_b), &sk_a.public(), Some(id_a), rb).unwrap();
+        let ra = a.ephemeral_public_key().clone();
+        let rb = b.ephemeral_public_key().clone();
+        let ka = a.compute_shared_key(&rb, 16).unwrap();
+        let kb = b.compute_shared_key(&ra, 16).unwrap();
+
+        assert_eq!(ka.key, hex!("f02f9068ad13e14f2b2602e0dfb2504f"));
+        assert_eq!(kb.key, hex!("f02f9068ad13e14f2b2602e0dfb2504f"));
+        assert_eq!(kb.confirmation(), &hex!("b93374ade30a74e12ddb40e4c03d0c6fcf61badbf2c2c5cc39a91201d9228e2e"));
+        assert_eq!(ka.confirmation(), &hex!("daefca6c32f53c48444d4ef35f98471e5d1cd1e3b5b8e3322dede310306689f6"));
+    }
+
+    #[test]
+    fn test_key_exchange_mismatch() {
+        let mut rng = rand::rng();
+        let sk_a = PrivateKey::new(&mut rng);
+        let sk_b = PrivateKey::new(&mut rng);
+        let pk_a = sk_a.public();
+        let pk_b = sk_b.public();
+
+        // B uses a different id for A.
+        let a = KeyExchange::new(KeyExchangeRole::Initiator, &sk_a, None, &pk_b, None, &mut rng).unwrap();
+        let b = KeyExchange::new(KeyExchangeRole::Responder, &sk_b, None, &pk_a, Some(b"alice"), &mut rng).unwrap();
+        let ra = a.ephemeral_public_key().clone();
+        let rb = b.ephemeral_public_key().clone();
+        let kb = b.compute_shared_key(&ra, 16).unwrap();
+        let ka = a.compute_shared_key(&rb, 16).unwrap();
+        assert_ne!(ka.key, kb.key);
+        assert!(ka.verify_peer_confirmation(kb.confirmation()).is_err());
+        assert!(kb.verify_peer_confirmation(ka.confirmation()).is_err());
+        assert!(ka.verify_peer_confirmation(&[0u8; 31]).is_err());
+    }
+
+    #[test]
+    fn test_x_bar() {
+        let x = U256 { v: [u64::MAX; 4] };
+        assert_eq!(x_bar(&x).v, [u64::MAX, u64::MAX, 0, 0]);
+        let x = U256 { v: [1, 2, 3, 4] };
+        assert_eq!(x_bar(&x).v, [1, 2 | (1 << 63), 0, 0]);
+    }
+}
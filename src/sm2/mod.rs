@@ -1,18 +1,21 @@
 use core::cell::RefCell;
 
-use ec::{curve::*, LIMB, NLIMBS};
+use alloc::vec::Vec;
+use ec::{curve::*, gfn::GFnElement, LIMB, NLIMBS};
+use error::{Result, SM2Error};
 use rand::Rng;
 mod ec;
 mod encrypt;
-mod key_exchange;
 
+pub mod key_exchange;
 pub mod sign;
 pub mod error;
 
 pub use encrypt::*;
+pub use key_exchange::*;
 pub use sign::*;
 
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct U256 {
     // little-endian representation of 256 bits integer.
     pub v: [LIMB; NLIMBS],
@@ -79,16 +82,71 @@ impl From<[u64; 4]> for U256 {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PublicKey {
     pub x: U256,
     pub y: U256,
 }
 
+/// The SEC1 point encodings:
+/// uncompressed 04 || x || y, compressed 02/03 || x and hybrid 06/07 || x || y,
+/// where 03 and 07 mark an odd y.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointFormat {
+    Uncompressed,
+    Compressed,
+    Hybrid,
+}
+
 impl PublicKey {
-    // TODO
+    /// Returns if x, y are in [0, p) and the point (x, y) is on the curve.
+    /// The infinity has no affine coordinates and is never valid.
     pub fn is_valid(&self) -> bool {
-        return true;
+        AffinePoint::try_new(self.x, self.y).is_some()
+    }
+
+    pub fn to_sec1_bytes(&self, format: PointFormat) -> Vec<u8> {
+        let y_odd = (self.y.v[0] & 1) as u8;
+        let mut out = Vec::with_capacity(65);
+        match format {
+            PointFormat::Uncompressed => out.push(4),
+            PointFormat::Compressed => out.push(2 | y_odd),
+            PointFormat::Hybrid => out.push(6 | y_odd),
+        }
+        out.extend_from_slice(&self.x.to_be_bytes());
+        if format != PointFormat::Compressed {
+            out.extend_from_slice(&self.y.to_be_bytes());
+        }
+        out
+    }
+
+    /// Parses any of the SEC1 encodings, the point is always validated.
+    pub fn from_sec1_bytes(data: &[u8]) -> Result<PublicKey> {
+        let tag = *data.first().ok_or(SM2Error::InvalidPointLength(1, 0))?;
+        let want = match tag {
+            2 | 3 => 33,
+            4 | 6 | 7 => 65,
+            _ => return Err(SM2Error::UnsupportedPointFormat(tag)),
+        };
+        if data.len() != want {
+            return Err(SM2Error::InvalidPointLength(want, data.len()));
+        }
+        let x = U256::from_be_slice(&data[1..33]).unwrap();
+        if want == 33 {
+            let p = AffinePoint::decompress(x, tag == 3).ok_or(SM2Error::InvalidPoint)?;
+            return Ok(PublicKey { x, y: U256::from(p.y) });
+        }
+
+        let y = U256::from_be_slice(&data[33..65]).unwrap();
+        // The hybrid form carries both y and its parity, they must agree.
+        if tag != 4 && (y.v[0] & 1) as u8 != tag & 1 {
+            return Err(SM2Error::InvalidPoint);
+        }
+        let public_key = PublicKey { x, y };
+        if !public_key.is_valid() {
+            return Err(SM2Error::InvalidPoint);
+        }
+        Ok(public_key)
     }
 }
 
@@ -113,6 +171,25 @@ impl PrivateKey {
         }
     }
 
+    /// Creates a private key from the 32 bytes big-endian scalar d,
+    /// d must be in [1, n-2].
+    pub fn from_be_slice(d: &[u8]) -> Result<Self> {
+        let d = U256::from_be_slice(d).ok_or(SM2Error::InvalidPrivateKey)?;
+        if !GFnElement::is_valid_private_key(&d) {
+            return Err(SM2Error::InvalidPrivateKey);
+        }
+        Ok(PrivateKey {
+            d,
+            d1inv: None,
+            public_key: RefCell::new(None),
+        })
+    }
+
+    /// Returns the scalar d in 32 bytes big-endian.
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        self.d.to_be_bytes()
+    }
+
     /// returns the public key.
     pub fn public(&self) -> PublicKey {
         if self.public_key.borrow().is_none() {
@@ -182,4 +259,85 @@ impl PrivateKey {
 // BM_scalarBaseMult/min_time:1.000       7590 ns         7570 ns       179677 items_per_second=132.099k/s
 // BM_scalarMult/min_time:1.000          61629 ns        61592 ns        23012 items_per_second=16.2358k/s
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_sec1_encoding() {
+        // generated by openssl genpkey -algorithm SM2.
+        let uncompressed = hex!("04a36703f50976815394c468dc8084878608f111016064e84dbb4cf9dc66590857af1af61eeabd2305e7671b4e8303763483b029df854b7aad3e3f09bd3847eaaf");
+        let pk = PublicKey::from_sec1_bytes(&uncompressed).unwrap();
+        assert_eq!(pk.to_sec1_bytes(PointFormat::Uncompressed), uncompressed);
+
+        let compressed = pk.to_sec1_bytes(PointFormat::Compressed);
+        assert_eq!(compressed[0], 3);
+        assert_eq!(compressed[1..], uncompressed[1..33]);
+        assert_eq!(PublicKey::from_sec1_bytes(&compressed).unwrap(), pk);
+
+        let hybrid = pk.to_sec1_bytes(PointFormat::Hybrid);
+        assert_eq!(hybrid[0], 7);
+        assert_eq!(hybrid[1..], uncompressed[1..]);
+        assert_eq!(PublicKey::from_sec1_bytes(&hybrid).unwrap(), pk);
+
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            let pk = PrivateKey::new(&mut rng).public();
+            for format in [PointFormat::Uncompressed, PointFormat::Compressed, PointFormat::Hybrid] {
+                assert_eq!(PublicKey::from_sec1_bytes(&pk.to_sec1_bytes(format)).unwrap(), pk);
+            }
+        }
+    }
+
+    #[test]
+    fn test_private_key_from_be_slice() {
+        let d = hex!("2ac464aef2460dbd9c2230812ebaf66a5def844a1743d0ebed7dba8594519e11");
+        let sk = PrivateKey::from_be_slice(&d).unwrap();
+        assert_eq!(sk.to_be_bytes(), d);
+        assert_eq!(
+            sk.public().to_sec1_bytes(PointFormat::Uncompressed),
+            hex!("04a36703f50976815394c468dc8084878608f111016064e84dbb4cf9dc66590857af1af61eeabd2305e7671b4e8303763483b029df854b7aad3e3f09bd3847eaaf")
+        );
+
+        let n_minus_2 = hex!("FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFF7203DF6B21C6052B53BBF40939D54121");
+        assert!(PrivateKey::from_be_slice(&n_minus_2).is_ok());
+        let n_minus_1 = hex!("FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFF7203DF6B21C6052B53BBF40939D54122");
+        assert_eq!(PrivateKey::from_be_slice(&n_minus_1).err(), Some(SM2Error::InvalidPrivateKey));
+        assert_eq!(PrivateKey::from_be_slice(&[0; 32]).err(), Some(SM2Error::InvalidPrivateKey));
+        assert_eq!(PrivateKey::from_be_slice(&d[1..]).err(), Some(SM2Error::InvalidPrivateKey));
+    }
+
+    #[test]
+    fn test_sec1_encoding_errors() {
+        let uncompressed = hex!("04a36703f50976815394c468dc8084878608f111016064e84dbb4cf9dc66590857af1af61eeabd2305e7671b4e8303763483b029df854b7aad3e3f09bd3847eaaf");
+        assert_eq!(PublicKey::from_sec1_bytes(&[]), Err(SM2Error::InvalidPointLength(1, 0)));
+        assert_eq!(PublicKey::from_sec1_bytes(&[0]), Err(SM2Error::UnsupportedPointFormat(0)));
+        assert_eq!(PublicKey::from_sec1_bytes(&uncompressed[..64]), Err(SM2Error::InvalidPointLength(65, 64)));
+        assert_eq!(PublicKey::from_sec1_bytes(&uncompressed[..33]), Err(SM2Error::InvalidPointLength(65, 33)));
+
+        let mut compressed = uncompressed[..33].to_vec();
+        compressed[0] = 2;
+        assert_eq!(PublicKey::from_sec1_bytes(&compressed[..32]), Err(SM2Error::InvalidPointLength(33, 32)));
+        // the point with the even y.
+        let pk = PublicKey::from_sec1_bytes(&compressed).unwrap();
+        assert_eq!(pk.x.to_be_bytes(), uncompressed[1..33]);
+        assert_eq!(pk.y.v[0] & 1, 0);
+
+        // hybrid with a wrong parity.
+        let mut hybrid = uncompressed;
+        hybrid[0] = 6;
+        assert_eq!(PublicKey::from_sec1_bytes(&hybrid), Err(SM2Error::InvalidPoint));
+
+        // x >= p is out of range.
+        let mut x = [0xffu8; 33];
+        x[0] = 2;
+        assert_eq!(PublicKey::from_sec1_bytes(&x), Err(SM2Error::InvalidPoint));
+        x[1..].copy_from_slice(&hex!("FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF00000000FFFFFFFFFFFFFFFF"));
+        assert_eq!(PublicKey::from_sec1_bytes(&x), Err(SM2Error::InvalidPoint));
+        // 2^3 - 3*2 + b is not a square.
+        let mut x = [0u8; 33];
+        x[0] = 3;
+        x[32] = 2;
+        assert_eq!(PublicKey::from_sec1_bytes(&x), Err(SM2Error::InvalidPoint));
+    }
+}
@@ -40,6 +40,14 @@ fn sign_inner(e: &[u8; 32], d: &PrivateKey, k: &[u64; 4]) -> Option<Signature> {
 }
 
 pub fn verify(e: &[u8; 32], pk: &PublicKey, sig: &Signature) -> bool {
+    let mut p = match JacobianPoint::try_new(pk.x, pk.y) {
+        Some(p) => p,
+        None => return false,
+    };
+    // GB/T 32918.2 7.1 B1, B2: r, s in [1, n-1], or r+n and s+n verify too.
+    if !GFnElement::is_valid_scalar(&sig.r) || !GFnElement::is_valid_scalar(&sig.s) {
+        return false;
+    }
     let e = GFnElement::from(e);
     let r = GFnElement::from(sig.r);
     let s = GFnElement::from(sig.s);
@@ -47,12 +55,15 @@ pub fn verify(e: &[u8; 32], pk: &PublicKey, sig: &Signature) -> bool {
     // let mut t:GFnElement = r;
     // t.add(&s);
     let t = GFnElement::new_from_add(&s, &r);
-    let mut p = JacobianPoint::from((pk.x, pk.y));
 
     // p = [s]G + [t]PK
     p.scalar_mul(&t.limbs);
     p.add(&JacobianPoint::new_from_scalar_base_mul(&s.limbs));
-    let x1 = GFnElement::from(&*p.get_affine_x().unwrap().transform_from_mont()).add_move(&e);
+    let mut x1 = match p.get_affine_x() {
+        Some(x1) => x1,
+        None => return false,
+    };
+    let x1 = GFnElement::from(&*x1.transform_from_mont()).add_move(&e);
 
     x1.limbs.ct_eq(&r.limbs).into()
 }
@@ -97,6 +108,7 @@ mod tests {
     use std::time::SystemTime;
     extern crate test;
     use super::*;
+    use hex_literal::hex;
     use rand::Rng;
 
     #[test]
@@ -112,6 +124,40 @@ mod tests {
         assert!(ok);
     }
 
+    #[test]
+    fn test_verify_out_of_range() {
+        // d, e and k are chosen to have r = s = 1, so r+n and s+n fit in 256 bits.
+        let sk = PrivateKey::from_be_slice(&hex!("889119a1aab33bc44c80089119a22ab2f4c63c3599741c37d49135c8e96aa922")).unwrap();
+        let pk = sk.public();
+        let e = hex!("ccc8b0e7ced862a28ff56901ef005dc682096d4ac0dda6350855e4bd3382c394");
+        let k = U256::from_be_slice(&hex!("1122334455667788990011223344556677889900112233445566778899001122")).unwrap();
+        let sig = sign_inner(&e, &sk, &k.v).unwrap();
+        assert_eq!((sig.r.v, sig.s.v), ([1, 0, 0, 0], [1, 0, 0, 0]));
+        assert!(verify(&e, &pk, &sig));
+
+        let mut n_plus_1 = U256::from(GFnElement::N);
+        n_plus_1.v[0] += 1;
+        assert!(!verify(&e, &pk, &Signature { r: n_plus_1, s: sig.s }));
+        assert!(!verify(&e, &pk, &Signature { r: sig.r, s: n_plus_1 }));
+        assert!(!verify(&e, &pk, &Signature { r: U256::default(), s: sig.s }));
+        assert!(!verify(&e, &pk, &Signature { r: sig.r, s: U256::default() }));
+        assert!(!verify(&e, &pk, &Signature { r: U256::from(GFnElement::N), s: sig.s }));
+    }
+
+    #[test]
+    fn test_verify_invalid_public_key() {
+        let mut rng = rand::rng();
+        let sk = PrivateKey::new(&mut rng);
+        let pk = sk.public();
+
+        let e = [1u8; 32];
+        let sig = sign_inner(&e, &sk, &rng.random()).unwrap();
+        let mut bad = pk.clone();
+        bad.y.v[0] ^= 1;
+        assert!(!verify(&e, &bad, &sig));
+        assert!(!verify(&e, &PublicKey { x: U256::default(), y: U256::default() }, &sig));
+    }
+
     #[test]
     fn test_sign_speed() {
         let sk = PrivateKey::new(&mut rand::rng());
@@ -30,6 +30,25 @@ use core::fmt;
 pub const BLOCK_SIZE: usize = 64;
 pub const DIGEST_SIZE: usize = 32;
 
+// The marshaled state of Digest, the same as the Go's sm3 of gmsm:
+// magic || s (8 words, big-endian) || x (nx bytes, zero padded to BLOCK_SIZE) || len (big-endian).
+const MAGIC: &[u8; 4] = b"sm3\x03";
+pub const MARSHALED_SIZE: usize = MAGIC.len() + 8 * 4 + BLOCK_SIZE + 8;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("sm3: invalid hash state identifier")]
+    InvalidStateIdentifier,
+
+    #[error("sm3: invalid hash state size, want {}, got {}", .0, .1)]
+    InvalidStateSize(usize, usize),
+
+    #[error("sm3: inconsistent hash state")]
+    InconsistentState,
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
 pub fn new()-> Digest{
     Digest::new()
 }
@@ -90,6 +109,15 @@ impl Digest {
         d
     }
 
+    // continues from the state s after len bytes, len is a multiple of BLOCK_SIZE.
+    pub(crate) fn from_state(s: [u32; 8], len: u64) -> Digest {
+        debug_assert_eq!(len % BLOCK_SIZE as u64, 0);
+        let mut d = Digest::new();
+        d.s = s;
+        d.len = len;
+        d
+    }
+
     pub fn reset(&mut self) -> &Digest {
         self.s = [0x7380166f, 0x4914b2b9, 0x172442d7, 0xda8a0600, 0xa96f30bc, 0x163138aa, 0xe38dee4d, 0xb0fb0e4e];
         self.x = [0; BLOCK_SIZE];
@@ -169,6 +197,49 @@ impl Digest {
         self.sum_into(&mut result);
         result
     }
+
+    // Returns the intermediate state, to be resumed by unmarshal, maybe in
+    // another process.
+    pub fn marshal(&self) -> [u8; MARSHALED_SIZE] {
+        let mut b = [0u8; MARSHALED_SIZE];
+        let (magic, rest) = b.split_at_mut(MAGIC.len());
+        let (s, rest) = rest.split_at_mut(8 * 4);
+        let (x, len) = rest.split_at_mut(BLOCK_SIZE);
+        magic.copy_from_slice(MAGIC);
+        for (b, w) in s.chunks_exact_mut(4).zip(&self.s) {
+            b.copy_from_slice(&w.to_be_bytes());
+        }
+        x[..self.nx].copy_from_slice(&self.x[..self.nx]);
+        len.copy_from_slice(&self.len.to_be_bytes());
+        b
+    }
+
+    // Restores the state marshaled. The buffered bytes are len % BLOCK_SIZE,
+    // the bytes after them must be zero.
+    pub fn unmarshal(&mut self, b: &[u8]) -> Result<()> {
+        if b.len() < MAGIC.len() || &b[..MAGIC.len()] != MAGIC {
+            return Err(Error::InvalidStateIdentifier);
+        }
+        if b.len() != MARSHALED_SIZE {
+            return Err(Error::InvalidStateSize(MARSHALED_SIZE, b.len()));
+        }
+        let (s, rest) = b[MAGIC.len()..].split_at(8 * 4);
+        let (x, len) = rest.split_at(BLOCK_SIZE);
+        let len = u64::from_be_bytes(len.try_into().unwrap());
+        let nx = (len % BLOCK_SIZE as u64) as usize;
+        if x[nx..].iter().any(|b| *b != 0) {
+            return Err(Error::InconsistentState);
+        }
+
+        for (w, b) in self.s.iter_mut().zip(s.chunks_exact(4)) {
+            *w = u32::from_be_bytes(b.try_into().unwrap());
+        }
+        self.x = [0; BLOCK_SIZE];
+        self.x[..nx].copy_from_slice(&x[..nx]);
+        self.nx = nx;
+        self.len = len;
+        Ok(())
+    }
 }
 
 // (l, h) = h<<32 + l
@@ -285,6 +356,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_marshal() {
+        let msg: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        let wanted = sm3!(&msg);
+        for split in [0, 1, 3, 63, 64, 65, 130, 300] {
+            let mut d = Digest::new();
+            d.write(&msg[..split]);
+            let state = d.marshal();
+
+            let mut r = Digest::new();
+            r.write(b"to be overwritten");
+            r.unmarshal(&state).unwrap();
+            assert_eq!(r.marshal(), state);
+            r.write(&msg[split..]);
+            assert_eq!(r.sum(), wanted, "{}", split);
+        }
+
+        let mut d = Digest::new();
+        d.write(b"abc");
+        let mut state = [0u8; MARSHALED_SIZE];
+        state[..4].copy_from_slice(b"sm3\x03");
+        state[4..36].copy_from_slice(&hex_literal::hex!("7380166f4914b2b9172442d7da8a0600a96f30bc163138aae38dee4db0fb0e4e"));
+        state[36..39].copy_from_slice(b"abc");
+        state[100..].copy_from_slice(&3u64.to_be_bytes());
+        assert_eq!(d.marshal(), state);
+        assert_eq!(Digest::new_with_default_id().marshal()[100..], 146u64.to_be_bytes());
+    }
+
+    #[test]
+    fn test_unmarshal_errors() {
+        let mut d = Digest::new();
+        d.write(b"abc");
+        let state = d.marshal();
+
+        let mut r = Digest::new();
+        assert_eq!(r.unmarshal(&state[..MARSHALED_SIZE - 1]), Err(Error::InvalidStateSize(MARSHALED_SIZE, MARSHALED_SIZE - 1)));
+        assert_eq!(r.unmarshal(&[state.as_slice(), &[0]].concat()), Err(Error::InvalidStateSize(MARSHALED_SIZE, MARSHALED_SIZE + 1)));
+        assert_eq!(r.unmarshal(&state[..2]), Err(Error::InvalidStateIdentifier));
+
+        let mut bad = state;
+        bad[3] = 2;
+        assert_eq!(r.unmarshal(&bad), Err(Error::InvalidStateIdentifier));
+
+        // len says 2 bytes buffered, but there are 3.
+        let mut bad = state;
+        bad[MARSHALED_SIZE - 1] = 2;
+        assert_eq!(r.unmarshal(&bad), Err(Error::InconsistentState));
+
+        // the failed unmarshal does not change the state.
+        assert_eq!(r.marshal(), Digest::new().marshal());
+    }
+
     // cargo test --release --package opengm_crypto --lib -- sm3::tests::test_bench --exact --show-output 
     #[test]
     fn test_bench() {
@@ -462,6 +462,16 @@ pub fn sum4(m0: &[u8], m1: &[u8], m2: &[u8], m3: &[u8]) -> [[u8; 32]; 4] {
     compressor.dump()
 }
 
+// compress one block for each lane, state[i] is the i-th word of the lanes.
+#[target_feature(enable = "neon")]
+pub unsafe fn compress_lanes(state: &mut [[u32; 4]; 8], m: &[&[u8]; 4]) {
+    unsafe {
+        let mut iv: [uint32x4_t; 8] = transmute(*state);
+        compress_block_aarch64_neon(&mut iv, m[0], m[1], m[2], m[3], 0b1111);
+        *state = transmute(iv);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::vec::Vec;
@@ -1,3 +1,3 @@
-mod sse2;
-mod avx2;
-mod avx512;
\ No newline at end of file
+pub mod sse2;
+pub mod avx2;
+pub mod avx512;
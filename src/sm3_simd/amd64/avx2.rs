@@ -168,9 +168,9 @@ pub unsafe fn load_message(m: &[&[u8]; 8]) -> [__m256i; 16] {
 
         // load messages to w[0..16]
         for i in 0..8 {
-            w[i] = _mm256_shuffle_epi8(_mm256_loadu_epi32(m[i].as_ptr() as *const i32), FLIP32);
+            w[i] = _mm256_shuffle_epi8(_mm256_loadu_si256(m[i].as_ptr() as *const __m256i), FLIP32);
             w[8 + i] = _mm256_shuffle_epi8(
-                _mm256_loadu_epi32(m[i].as_ptr().offset(32) as *const i32),
+                _mm256_loadu_si256(m[i].as_ptr().offset(32) as *const __m256i),
                 FLIP32,
             );
         }
@@ -352,6 +352,16 @@ pub fn sum_equal8(m: &[&[u8]; 8]) -> [[u8; 32]; 8] {
     compressor.dump()
 }
 
+// compress one block for each lane, state[i] is the i-th word of the lanes.
+#[target_feature(enable = "avx2", enable = "avx")]
+pub unsafe fn compress_lanes(state: &mut [[u32; 8]; 8], m: &[&[u8]; 8]) {
+    unsafe {
+        let mut iv: [__m256i; 8] = transmute(*state);
+        compress(&mut iv, m);
+        *state = transmute(iv);
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -394,6 +394,16 @@ pub fn sum_equal16(m: &[&[u8];16]) -> [[u8; 32]; 16] {
     compressor.dump()
 }
 
+// compress one block for each lane, state[i] is the i-th word of the lanes.
+#[target_feature(enable = "avx512f", enable = "avx512vl", enable = "avx512bw")]
+pub unsafe fn compress_lanes(state: &mut [[u32; 16]; 8], m: &[&[u8]; 16]) {
+    unsafe {
+        let mut iv: [__m512i; 8] = transmute(*state);
+        compress(&mut iv, m);
+        *state = transmute(iv);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     
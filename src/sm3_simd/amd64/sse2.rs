@@ -342,6 +342,16 @@ pub unsafe fn sum_equal4(m: &[&[u8]; LANES]) -> [[u8; 32]; LANES] {
     unsafe { compressor.dump() }
 }
 
+// compress one block for each lane, state[i] is the i-th word of the lanes.
+#[target_feature(enable = "ssse3", enable = "sse2", enable = "sse")]
+pub unsafe fn compress_lanes(state: &mut [[u32; LANES]; 8], m: &[&[u8]; LANES]) {
+    unsafe {
+        let mut iv: [__m128i; 8] = transmute(*state);
+        compress(&mut iv, m);
+        *state = transmute(iv);
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
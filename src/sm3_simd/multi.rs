@@ -0,0 +1,246 @@
+// Multi-buffer SM3: hashes many independent messages, one message per SIMD
+// lane. A lane takes the next message as soon as its message is done, so the
+// messages are of any lengths. The longest messages are scheduled first, and
+// the last few are left to sm3::Digest once less than half lanes are busy.
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use crate::sm3::{self, BLOCK_SIZE, DIGEST_SIZE};
+
+#[cfg(target_arch = "aarch64")]
+use super::aarch64;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use super::amd64::{avx2, avx512, sse2};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use crate::internal::cpuid::x86_64::*;
+
+// Returns the number of messages hashed in parallel, 1 if there is no SIMD.
+pub fn lanes() -> usize {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if support_avx512f() && support_avx512vl() && support_avx512bw() {
+            return 16;
+        } else if support_avx2() && support_avx() {
+            return 8;
+        } else if support_ssse3() {
+            return 4;
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    return 4;
+
+    #[allow(unreachable_code)]
+    1
+}
+
+// Returns the digests of msgs, in the order of msgs.
+pub fn sum_many<M: AsRef<[u8]>>(msgs: &[M]) -> Vec<[u8; DIGEST_SIZE]> {
+    let mut digests = vec![[0; DIGEST_SIZE]; msgs.len()];
+    sum_many_into(msgs, &mut digests);
+    digests
+}
+
+// Writes the digest of msgs[i] into digests[i].
+// Panics if the lengths of msgs and digests differ.
+pub fn sum_many_into<M: AsRef<[u8]>>(msgs: &[M], digests: &mut [[u8; DIGEST_SIZE]]) {
+    assert_eq!(msgs.len(), digests.len(), "sm3_simd: one digest for each message");
+    let msgs: Vec<&[u8]> = msgs.iter().map(|m| m.as_ref()).collect();
+    match lanes() {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        16 => sum_lanes(|s, m| unsafe { avx512::compress_lanes(s, m) }, &msgs, digests),
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        8 => sum_lanes(|s, m| unsafe { avx2::compress_lanes(s, m) }, &msgs, digests),
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        4 => sum_lanes(|s, m| unsafe { sse2::compress_lanes(s, m) }, &msgs, digests),
+        #[cfg(target_arch = "aarch64")]
+        4 => sum_lanes(|s, m| unsafe { aarch64::compress_lanes(s, m) }, &msgs, digests),
+        _ => {
+            for (m, d) in msgs.iter().zip(digests) {
+                *d = sm3::Digest::new().write(m).sum();
+            }
+        }
+    }
+}
+
+// A message in a lane, compressed block by block.
+struct Lane<'a> {
+    index: usize,
+    msg: &'a [u8],
+    // bytes of msg compressed, a multiple of BLOCK_SIZE.
+    done: usize,
+    // the last bytes of msg padded, 1 or 2 blocks, built after the full blocks.
+    tail: [u8; 2 * BLOCK_SIZE],
+    tail_blocks: usize,
+    tail_done: usize,
+}
+
+impl<'a> Lane<'a> {
+    fn new(index: usize, msg: &'a [u8]) -> Self {
+        Lane { index, msg, done: 0, tail: [0; 2 * BLOCK_SIZE], tail_blocks: 0, tail_done: 0 }
+    }
+
+    fn in_tail(&self) -> bool {
+        self.msg.len() - self.done < BLOCK_SIZE
+    }
+
+    // builds the tail once the full blocks are done.
+    fn prepare(&mut self) {
+        if !self.in_tail() || self.tail_blocks > 0 {
+            return;
+        }
+        let n = self.msg.len() - self.done;
+        self.tail[..n].copy_from_slice(&self.msg[self.done..]);
+        self.tail[n] = 0x80;
+        self.tail_blocks = if n + 1 + 8 <= BLOCK_SIZE { 1 } else { 2 };
+        let end = self.tail_blocks * BLOCK_SIZE;
+        self.tail[end - 8..end].copy_from_slice(&(self.msg.len() as u64 * 8).to_be_bytes());
+    }
+
+    fn block(&self) -> &[u8] {
+        if self.in_tail() {
+            &self.tail[self.tail_done * BLOCK_SIZE..]
+        } else {
+            &self.msg[self.done..]
+        }
+    }
+
+    // moves to the next block, returns true if the message is done.
+    fn advance(&mut self) -> bool {
+        if self.in_tail() {
+            self.tail_done += 1;
+            self.tail_done == self.tail_blocks
+        } else {
+            self.done += BLOCK_SIZE;
+            false
+        }
+    }
+}
+
+// compress is the compression function of N lanes, the state is
+// [[word 0 of the lanes], ..., [word 7 of the lanes]].
+fn sum_lanes<const N: usize, F>(mut compress: F, msgs: &[&[u8]], digests: &mut [[u8; DIGEST_SIZE]])
+where
+    F: FnMut(&mut [[u32; N]; 8], &[&[u8]; N]),
+{
+    // the idle lanes compress the dummy block.
+    const DUMMY: [u8; BLOCK_SIZE] = [0; BLOCK_SIZE];
+    let iv = sm3::Digest::new().s;
+
+    let mut order: Vec<usize> = (0..msgs.len()).collect();
+    order.sort_unstable_by_key(|&i| Reverse(msgs[i].len()));
+    let mut queue = order.into_iter();
+
+    let mut state = [[0u32; N]; 8];
+    let mut lanes: [Option<Lane>; N] = core::array::from_fn(|_| None);
+    loop {
+        for (k, lane) in lanes.iter_mut().enumerate() {
+            if lane.is_none() && let Some(i) = queue.next() {
+                *lane = Some(Lane::new(i, msgs[i]));
+                state.iter_mut().zip(&iv).for_each(|(s, v)| s[k] = *v);
+            }
+        }
+
+        // leave the rest to sm3::Digest, except that a lane between its two
+        // tail blocks is done in the next round.
+        let active = lanes.iter().flatten().count();
+        if queue.len() == 0
+            && active * 2 <= N
+            && lanes.iter().flatten().all(|l| l.tail_done == 0)
+        {
+            for (k, lane) in lanes.iter().enumerate() {
+                if let Some(l) = lane {
+                    let s = core::array::from_fn(|j| state[j][k]);
+                    let mut d = sm3::Digest::from_state(s, l.done as u64);
+                    d.write(&l.msg[l.done..]);
+                    digests[l.index] = d.sum();
+                }
+            }
+            return;
+        }
+
+        lanes.iter_mut().flatten().for_each(|l| l.prepare());
+        let blocks = core::array::from_fn(|k| lanes[k].as_ref().map_or(&DUMMY[..], |l| l.block()));
+        compress(&mut state, &blocks);
+
+        for (k, lane) in lanes.iter_mut().enumerate() {
+            if let Some(l) = lane && l.advance() {
+                for (j, s) in state.iter().enumerate() {
+                    digests[l.index][4 * j..4 * j + 4].copy_from_slice(&s[k].to_be_bytes());
+                }
+                *lane = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rng, Rng};
+    use std::vec::Vec;
+
+    fn messages(count: usize, max_len: usize) -> Vec<Vec<u8>> {
+        let mut r = rng();
+        (0..count)
+            .map(|_| {
+                let mut m = vec![0u8; r.random_range(0..=max_len)];
+                r.fill(m.as_mut_slice());
+                m
+            })
+            .collect()
+    }
+
+    fn sm3_each(msgs: &[Vec<u8>]) -> Vec<[u8; DIGEST_SIZE]> {
+        msgs.iter().map(|m| sm3::Digest::new().write(m).sum()).collect()
+    }
+
+    #[test]
+    fn test_sum_many() {
+        assert!(sum_many::<&[u8]>(&[]).is_empty());
+        assert_eq!(sum_many(&[b"abc"]), [sm3!(b"abc")]);
+
+        for count in [1, 2, 3, 4, 5, 7, 8, 9, 15, 16, 17, 33, 100] {
+            let msgs = messages(count, 300);
+            assert_eq!(sum_many(&msgs), sm3_each(&msgs), "{}", count);
+        }
+
+        // the lengths around the tail of one and two blocks.
+        let msgs: Vec<Vec<u8>> = (0..=200).map(|n| vec![n as u8; n]).collect();
+        assert_eq!(sum_many(&msgs), sm3_each(&msgs));
+
+        // a long message among the short ones.
+        let mut msgs = messages(40, 100);
+        msgs[17] = vec![0x5a; 10000];
+        assert_eq!(sum_many(&msgs), sm3_each(&msgs));
+    }
+
+    #[test]
+    fn test_sum_lanes() {
+        let msgs = messages(50, 300);
+        let refs: Vec<&[u8]> = msgs.iter().map(|m| m.as_slice()).collect();
+        let wanted = sm3_each(&msgs);
+        let mut digests = vec![[0; DIGEST_SIZE]; msgs.len()];
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if support_ssse3() {
+                sum_lanes(|s, m| unsafe { sse2::compress_lanes(s, m) }, &refs, &mut digests);
+                assert_eq!(digests, wanted);
+            }
+            if support_avx2() && support_avx() {
+                sum_lanes(|s, m| unsafe { avx2::compress_lanes(s, m) }, &refs, &mut digests);
+                assert_eq!(digests, wanted);
+            }
+            if support_avx512f() && support_avx512vl() && support_avx512bw() {
+                sum_lanes(|s, m| unsafe { avx512::compress_lanes(s, m) }, &refs, &mut digests);
+                assert_eq!(digests, wanted);
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            sum_lanes(|s, m| unsafe { aarch64::compress_lanes(s, m) }, &refs, &mut digests);
+            assert_eq!(digests, wanted);
+        }
+    }
+}
@@ -178,6 +178,29 @@ impl Block for sm4::Cipher {
 
 
 
+// A borrowed block cipher, so that a mode can be built on the fly, e.g., the CTR in GCM.
+impl<B: Block> Block for &B {
+    fn block_size(&self) -> usize {
+        (**self).block_size()
+    }
+
+    fn encrypt(&self, dst: &mut [u8], src: &[u8]) -> usize {
+        (**self).encrypt(dst, src)
+    }
+
+    fn decrypt(&self, dst: &mut [u8], src: &[u8]) -> usize {
+        (**self).decrypt(dst, src)
+    }
+
+    fn encrypt_inplace(&self, in_out: &mut [u8]) -> usize {
+        (**self).encrypt_inplace(in_out)
+    }
+
+    fn decrypt_inplace(&self, in_out: &mut [u8]) -> usize {
+        (**self).decrypt_inplace(in_out)
+    }
+}
+
 pub trait AEAD {
     type Error;
 
@@ -0,0 +1,431 @@
+use alloc::vec::Vec;
+use num::BigInt;
+
+use crate::cms::sm2::ASN1Decode;
+use crate::cryptobyte::asn1::{ObjectIdentifier, Time};
+use crate::cryptobyte::oid::{OidCommonName, OidSignatureSM2WithSM3};
+use crate::cryptobyte::{self, Parser, Tag, BIT_STRING, NULL, SEQUENCE, SET};
+use crate::sm2::{self, PublicKey};
+
+use super::{parse_pkix_public_key, Error, Result};
+
+const VERSION_TAG: Tag = Tag(0).constructed().context_specific();
+const ISSUER_UNIQUE_ID_TAG: Tag = Tag(1).context_specific();
+const SUBJECT_UNIQUE_ID_TAG: Tag = Tag(2).context_specific();
+const EXTENSIONS_TAG: Tag = Tag(3).constructed().context_specific();
+
+// AttributeTypeAndValue ::= SEQUENCE {
+//     type   AttributeType,
+//     value  AttributeValue }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeTypeAndValue<'a> {
+    pub oid: ObjectIdentifier,
+    pub tag: Tag,
+    pub value: &'a [u8],
+}
+
+impl<'a> AttributeTypeAndValue<'a> {
+    // Returns the value of the string types, the BMPString and UniversalString
+    // are not supported.
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.tag {
+            cryptobyte::UTF8String | cryptobyte::PrintableString | cryptobyte::IA5String => {
+                core::str::from_utf8(self.value).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+// Name ::= SEQUENCE OF RelativeDistinguishedName
+// RelativeDistinguishedName ::= SET OF AttributeTypeAndValue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Name<'a> {
+    // the DER of the Name, to match an issuer with a subject.
+    pub raw: &'a [u8],
+    pub rdns: Vec<Vec<AttributeTypeAndValue<'a>>>,
+}
+
+impl<'a> Name<'a> {
+    // Returns the first string value of the attribute type.
+    pub fn attribute(&self, oid: &ObjectIdentifier) -> Option<&'a str> {
+        self.rdns.iter().flatten().find(|atv| atv.oid == *oid)?.as_str()
+    }
+
+    pub fn common_name(&self) -> Option<&'a str> {
+        self.attribute(&OidCommonName)
+    }
+}
+
+// Validity ::= SEQUENCE {
+//     notBefore  Time,
+//     notAfter   Time }
+// Time ::= CHOICE { utcTime UTCTime, generalTime GeneralizedTime }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Validity {
+    pub not_before: Time,
+    pub not_after: Time,
+}
+
+impl Validity {
+    // Both of the bounds are inclusive.
+    pub fn contains(&self, t: &Time) -> bool {
+        self.not_before <= *t && *t <= self.not_after
+    }
+}
+
+// Extension ::= SEQUENCE {
+//     extnID     OBJECT IDENTIFIER,
+//     critical   BOOLEAN DEFAULT FALSE,
+//     extnValue  OCTET STRING }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extension<'a> {
+    pub id: ObjectIdentifier,
+    pub critical: bool,
+    pub value: &'a [u8],
+}
+
+// Certificate ::= SEQUENCE {
+//     tbsCertificate      TBSCertificate,
+//     signatureAlgorithm  AlgorithmIdentifier,
+//     signatureValue      BIT STRING }
+//
+// TBSCertificate ::= SEQUENCE {
+//     version          [0] EXPLICIT Version DEFAULT v1,
+//     serialNumber         CertificateSerialNumber,
+//     signature            AlgorithmIdentifier,
+//     issuer               Name,
+//     validity             Validity,
+//     subject              Name,
+//     subjectPublicKeyInfo SubjectPublicKeyInfo,
+//     issuerUniqueID   [1] IMPLICIT UniqueIdentifier OPTIONAL,
+//     subjectUniqueID  [2] IMPLICIT UniqueIdentifier OPTIONAL,
+//     extensions       [3] EXPLICIT Extensions OPTIONAL }
+//
+// The certificate borrows the DER it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Certificate<'a> {
+    pub raw: &'a [u8],
+    pub raw_tbs_certificate: &'a [u8],
+    pub raw_subject_public_key_info: &'a [u8],
+
+    // 1, 2 or 3.
+    pub version: i64,
+    pub serial_number: BigInt,
+    pub issuer: Name<'a>,
+    pub validity: Validity,
+    pub subject: Name<'a>,
+    pub public_key: PublicKey,
+    pub extensions: Vec<Extension<'a>>,
+
+    pub signature_algorithm: ObjectIdentifier,
+    // the content of the signatureValue BIT STRING.
+    pub signature: &'a [u8],
+}
+
+impl<'a> Certificate<'a> {
+    pub fn extension(&self, id: &ObjectIdentifier) -> Option<&Extension<'a>> {
+        self.extensions.iter().find(|ext| ext.id == *id)
+    }
+
+    // Verifies the SM2-with-SM3 signature over the TBSCertificate, with the
+    // default ID 1234567812345678 of GM/T 0009 for the Z value.
+    pub fn check_signature(&self, pk: &PublicKey) -> Result<()> {
+        if self.signature_algorithm != OidSignatureSM2WithSM3 {
+            return Err(Error::UnsupportedSignatureAlgorithm(self.signature_algorithm.to_string()));
+        }
+        let mut input = Parser::new(self.signature);
+        let sig = input.decode_sm2_signature().ok_or(Error::InvalidSignature)?;
+        if !input.empty() {
+            return Err(Error::InvalidSignature);
+        }
+        let e = sm2::precompute_with_id_public_key_msg(None, pk, self.raw_tbs_certificate);
+        if !sm2::verify(&e, pk, &sig) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    // Verifies that the certificate is signed by parent. Only the signature is
+    // checked, the names, validity and constraints are left to the chain building.
+    pub fn check_signature_from(&self, parent: &Certificate) -> Result<()> {
+        self.check_signature(&parent.public_key)
+    }
+}
+
+pub fn parse_certificate(der: &[u8]) -> Result<Certificate<'_>> {
+    let mut input = Parser::new(der);
+    let cert = input.read_asn1_object().filter(|obj| obj.tag == SEQUENCE).ok_or(Error::MalformedCertificate("certificate"))?;
+    if !input.empty() {
+        return Err(Error::TrailingData("Certificate"));
+    }
+    let mut cert_body = Parser::new(cert.value);
+
+    let tbs = cert_body.read_asn1_object().filter(|obj| obj.tag == SEQUENCE).ok_or(Error::MalformedCertificate("tbs certificate"))?;
+    let mut tbs_body = Parser::new(tbs.value);
+
+    let version = match tbs_body.read_optional_asn1(VERSION_TAG).ok_or(Error::MalformedCertificate("version"))? {
+        Some(v) => {
+            let mut v = Parser::new(v);
+            let version = v.read_asn1_i64().filter(|_| v.empty()).ok_or(Error::MalformedCertificate("version"))?;
+            if !(0..=2).contains(&version) {
+                return Err(Error::UnsupportedVersion("certificate", version));
+            }
+            version + 1
+        }
+        None => 1,
+    };
+
+    let serial_number = tbs_body.read_asn1_bigint().ok_or(Error::MalformedCertificate("serial number"))?;
+    let inner_algorithm = read_signature_algorithm(&mut tbs_body)?;
+    let issuer = read_name(&mut tbs_body).ok_or(Error::MalformedCertificate("issuer"))?;
+    let validity = read_validity(&mut tbs_body).ok_or(Error::MalformedCertificate("validity"))?;
+    let subject = read_name(&mut tbs_body).ok_or(Error::MalformedCertificate("subject"))?;
+
+    let spki = tbs_body.read_asn1_object().filter(|obj| obj.tag == SEQUENCE).ok_or(Error::MalformedCertificate("spki"))?;
+    let public_key = parse_pkix_public_key(spki.raw)?;
+
+    // the unique identifiers are deprecated, skipped.
+    if version > 1 {
+        tbs_body.read_optional_asn1(ISSUER_UNIQUE_ID_TAG).ok_or(Error::MalformedCertificate("issuer unique id"))?;
+        tbs_body.read_optional_asn1(SUBJECT_UNIQUE_ID_TAG).ok_or(Error::MalformedCertificate("subject unique id"))?;
+    }
+    let extensions = match tbs_body.read_optional_asn1(EXTENSIONS_TAG).ok_or(Error::MalformedCertificate("extensions"))? {
+        Some(exts) if version == 3 => read_extensions(exts)?,
+        Some(_) => return Err(Error::MalformedCertificate("extensions")),
+        None => Vec::new(),
+    };
+    if !tbs_body.empty() {
+        return Err(Error::TrailingData("TBSCertificate"));
+    }
+
+    let signature_algorithm = read_signature_algorithm(&mut cert_body)?;
+    if signature_algorithm != inner_algorithm {
+        return Err(Error::SignatureAlgorithmMismatch);
+    }
+    // the signature is always a whole number of bytes.
+    let signature = cert_body.read_asn1(BIT_STRING).filter(|v| v.first() == Some(&0)).ok_or(Error::MalformedCertificate("signature"))?;
+    if !cert_body.empty() {
+        return Err(Error::TrailingData("Certificate"));
+    }
+
+    Ok(Certificate {
+        raw: cert.raw,
+        raw_tbs_certificate: tbs.raw,
+        raw_subject_public_key_info: spki.raw,
+        version,
+        serial_number,
+        issuer,
+        validity,
+        subject,
+        public_key,
+        extensions,
+        signature_algorithm,
+        signature: &signature[1..],
+    })
+}
+
+// AlgorithmIdentifier ::= SEQUENCE {
+//     algorithm   OBJECT IDENTIFIER,
+//     parameters  ANY DEFINED BY algorithm OPTIONAL }
+// Only the absent or NULL parameters of the signature algorithms are accepted.
+fn read_signature_algorithm(input: &mut Parser) -> Result<ObjectIdentifier> {
+    let mut alg = input.read_asn1_sequence().ok_or(Error::MalformedAlgorithmIdentifier)?;
+    let oid = alg.read_asn1_object_identifier().ok_or(Error::MalformedAlgorithmIdentifier)?;
+    if !alg.empty() && (alg.read_asn1(NULL) != Some(&[]) || !alg.empty()) {
+        return Err(Error::MalformedAlgorithmIdentifier);
+    }
+    Ok(oid)
+}
+
+fn read_name<'a>(input: &mut Parser<'a>) -> Option<Name<'a>> {
+    let name = input.read_asn1_object().filter(|obj| obj.tag == SEQUENCE)?;
+    let mut seq = Parser::new(name.value);
+    let mut rdns = Vec::new();
+    while !seq.empty() {
+        let mut set = Parser::new(seq.read_asn1(SET)?);
+        let mut rdn = Vec::new();
+        while !set.empty() {
+            let mut atv = set.read_asn1_sequence()?;
+            let oid = atv.read_asn1_object_identifier()?;
+            let value = atv.read_asn1_object()?;
+            if !atv.empty() {
+                return None;
+            }
+            rdn.push(AttributeTypeAndValue { oid, tag: value.tag, value: value.value });
+        }
+        if rdn.is_empty() {
+            return None;
+        }
+        rdns.push(rdn);
+    }
+    Some(Name { raw: name.raw, rdns })
+}
+
+fn read_validity(input: &mut Parser) -> Option<Validity> {
+    let mut validity = input.read_asn1_sequence()?;
+    let mut read_time = || match validity.peek_tag()? {
+        cryptobyte::UTCTime => validity.read_asn1_utc_time(),
+        cryptobyte::GeneralizedTime => validity.read_asn1_generalized_time(),
+        _ => None,
+    };
+    let not_before = read_time()?;
+    let not_after = read_time()?;
+    if !validity.empty() {
+        return None;
+    }
+    Some(Validity { not_before, not_after })
+}
+
+// Extensions ::= SEQUENCE SIZE (1..MAX) OF Extension
+fn read_extensions(der: &[u8]) -> Result<Vec<Extension<'_>>> {
+    let malformed = || Error::MalformedCertificate("extension");
+    let mut input = Parser::new(der);
+    let mut exts = input.read_asn1_sequence().ok_or(malformed())?;
+    if !input.empty() || exts.empty() {
+        return Err(Error::MalformedCertificate("extensions"));
+    }
+
+    let mut extensions: Vec<Extension> = Vec::new();
+    while !exts.empty() {
+        let mut ext = exts.read_asn1_sequence().ok_or(malformed())?;
+        let id = ext.read_asn1_object_identifier().ok_or(malformed())?;
+        // DER forbids the encoding of the DEFAULT FALSE.
+        let critical = match ext.peek_tag() {
+            Some(cryptobyte::BOOLEAN) => ext.read_asn1_boolean().filter(|critical| *critical).ok_or(malformed())?,
+            _ => false,
+        };
+        let value = ext.read_asn1_octet_string().ok_or(malformed())?;
+        if !ext.empty() {
+            return Err(malformed());
+        }
+        if extensions.iter().any(|e| e.id == id) {
+            return Err(Error::DuplicateExtension(id.to_string()));
+        }
+        extensions.push(Extension { id, critical, value });
+    }
+    Ok(extensions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptobyte::oid::*;
+    use crate::encoding::pem;
+    use hex_literal::hex;
+
+    // openssl req -x509 -new -key ca.key -sm3 -sigopt distid:1234567812345678
+    //     -subj "/C=CN/O=Test CA/CN=SM2 Root" -set_serial 0x0102030405
+    //     -addext "basicConstraints=critical,CA:TRUE" -addext "keyUsage=critical,keyCertSign"
+    // openssl x509 -req -in leaf.csr -CA ca.pem -CAkey ca.key -sm3 -sigopt distid:1234567812345678 -set_serial 7
+    // the OpenSSL 3 signs with an empty ID without the distid.
+    const CERTS: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBujCCAWCgAwIBAgIFAQIDBAUwCgYIKoEcz1UBg3UwMjELMAkGA1UEBhMCQ04x
+EDAOBgNVBAoMB1Rlc3QgQ0ExETAPBgNVBAMMCFNNMiBSb290MB4XDTI2MTAxODA3
+NDkxMloXDTM2MTAxNTA3NDkxMlowMjELMAkGA1UEBhMCQ04xEDAOBgNVBAoMB1Rl
+c3QgQ0ExETAPBgNVBAMMCFNNMiBSb290MFkwEwYHKoZIzj0CAQYIKoEcz1UBgi0D
+QgAEKXdlPZEd4ROE889j+I1XTvjaBt70BurYauzpyibEvsrXusY2WWaDk5vdTtJk
+lvOEVj3YwG3wYUHTlY+P/LF06aNjMGEwHQYDVR0OBBYEFIzexfjKu7Lc9yY7cGg7
+odymxiYXMB8GA1UdIwQYMBaAFIzexfjKu7Lc9yY7cGg7odymxiYXMA8GA1UdEwEB
+/wQFMAMBAf8wDgYDVR0PAQH/BAQDAgIEMAoGCCqBHM9VAYN1A0gAMEUCIQD0uKuV
+pryYY4i27V+UKarK9QmnjmBzMXaOBYYkNJqduwIgVSfR8Fsp+zBsi7OhgcW8MvVr
+LMD4SimSovV8ic2T0QY=
+-----END CERTIFICATE-----
+-----BEGIN CERTIFICATE-----
+MIIBejCCASCgAwIBAgIBBzAKBggqgRzPVQGDdTAyMQswCQYDVQQGEwJDTjEQMA4G
+A1UECgwHVGVzdCBDQTERMA8GA1UEAwwIU00yIFJvb3QwHhcNMjYxMDE4MDc0OTE3
+WhcNMjcxMDE4MDc0OTE3WjAXMRUwEwYDVQQDDAxsZWFmLmV4YW1wbGUwWTATBgcq
+hkjOPQIBBggqgRzPVQGCLQNCAASjZwP1CXaBU5TEaNyAhIeGCPERAWBk6E27TPnc
+ZlkIV68a9h7qvSMF52cbToMDdjSDsCnfhUt6rT4/Cb04R+qvo0IwQDAdBgNVHQ4E
+FgQURdtqEpZpV05zmEuxrXqbKJUajBwwHwYDVR0jBBgwFoAUjN7F+Mq7stz3Jjtw
+aDuh3KbGJhcwCgYIKoEcz1UBg3UDSAAwRQIgfv4/hGbL9tqGb3qkMbGREON5kcat
+tBS5I3gBwv1ZR7wCIQD72Gn3l0mVI/f/58F0mHOqFKzY0+tscz7Hac303wttTQ==
+-----END CERTIFICATE-----
+";
+
+    fn certs() -> Vec<Vec<u8>> {
+        pem::decode_all(CERTS.as_bytes()).unwrap().into_iter().map(|b| b.bytes).collect()
+    }
+
+    #[test]
+    fn test_parse_certificate() {
+        let ders = certs();
+        let ca = parse_certificate(&ders[0]).unwrap();
+        let leaf = parse_certificate(&ders[1]).unwrap();
+
+        assert_eq!(ca.version, 3);
+        assert_eq!(ca.serial_number, BigInt::from(0x0102030405i64));
+        assert_eq!(ca.signature_algorithm, OidSignatureSM2WithSM3);
+        assert_eq!(ca.subject, ca.issuer);
+        assert_eq!(ca.subject.attribute(&OidCountry), Some("CN"));
+        assert_eq!(ca.subject.attribute(&OidOrganization), Some("Test CA"));
+        assert_eq!(ca.subject.common_name(), Some("SM2 Root"));
+        assert_eq!(ca.validity.not_before, Time::new(2026, 10, 18, 7, 49, 12).unwrap());
+        assert_eq!(ca.validity.not_after, Time::new(2036, 10, 15, 7, 49, 12).unwrap());
+        assert!(ca.validity.contains(&Time::from_unix(1792309752).unwrap()));
+        assert!(!ca.validity.contains(&Time::from_unix(1792309751).unwrap()));
+        assert_eq!(ca.extensions.len(), 4);
+        let bc = ca.extension(&OidExtensionBasicConstraints).unwrap();
+        assert!(bc.critical);
+        assert_eq!(bc.value, hex!("30030101ff"));
+        assert!(!ca.extension(&OidExtensionSubjectKeyId).unwrap().critical);
+
+        assert_eq!(leaf.serial_number, BigInt::from(7));
+        assert_eq!(leaf.issuer.raw, ca.subject.raw);
+        assert_eq!(leaf.subject.common_name(), Some("leaf.example"));
+        assert_eq!(leaf.public_key, parse_pkix_public_key(leaf.raw_subject_public_key_info).unwrap());
+        assert_eq!(leaf.public_key.x.to_be_bytes(), hex!("a36703f50976815394c468dc8084878608f111016064e84dbb4cf9dc66590857"));
+        assert_eq!(leaf.extension(&OidExtensionKeyUsage), None);
+        assert_eq!(leaf.raw, ders[1]);
+    }
+
+    #[test]
+    fn test_check_signature() {
+        let ders = certs();
+        let ca = parse_certificate(&ders[0]).unwrap();
+        let leaf = parse_certificate(&ders[1]).unwrap();
+
+        ca.check_signature_from(&ca).unwrap();
+        leaf.check_signature_from(&ca).unwrap();
+        assert!(matches!(leaf.check_signature_from(&leaf), Err(Error::InvalidSignature)));
+        assert!(matches!(ca.check_signature(&leaf.public_key), Err(Error::InvalidSignature)));
+
+        // CN=leaf.example -> CN=leaf.examplf
+        let mut der = ders[1].clone();
+        let i = der.windows(12).position(|w| w == b"leaf.example").unwrap();
+        der[i + 11] ^= 3;
+        let forged = parse_certificate(&der).unwrap();
+        assert!(matches!(forged.check_signature_from(&ca), Err(Error::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_parse_certificate_errors() {
+        let ders = certs();
+        let leaf = &ders[1];
+
+        assert!(matches!(parse_certificate(&leaf[..leaf.len() - 1]), Err(Error::MalformedCertificate(_))));
+
+        let mut der = leaf.clone();
+        der.push(0);
+        assert!(matches!(parse_certificate(&der), Err(Error::TrailingData(_))));
+
+        // the outer signature algorithm ecdsa-with-SHA256.
+        let mut der = leaf.clone();
+        let i = der.windows(10).rposition(|w| w == hex!("06082a811ccf55018375")).unwrap();
+        der[i..i + 10].copy_from_slice(&hex!("06082a8648ce3d040302"));
+        assert!(matches!(parse_certificate(&der), Err(Error::SignatureAlgorithmMismatch)));
+
+        // version 4.
+        let mut der = leaf.clone();
+        assert_eq!(der[8..13], hex!("a003020102"));
+        der[12] = 3;
+        assert!(matches!(parse_certificate(&der), Err(Error::UnsupportedVersion(_, 3))));
+
+        // duplicate the authorityKeyIdentifier as the subjectKeyIdentifier.
+        let mut der = leaf.clone();
+        let i = der.windows(5).position(|w| w == hex!("0603551d0e")).unwrap();
+        der[i + 4] = 0x23;
+        assert!(matches!(parse_certificate(&der), Err(Error::DuplicateExtension(_))));
+    }
+}
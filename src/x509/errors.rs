@@ -0,0 +1,73 @@
+use alloc::string::String;
+use thiserror;
+
+use crate::cryptobyte;
+use crate::sm2::error::SM2Error;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("x509: malformed algorithm identifier")]
+    MalformedAlgorithmIdentifier,
+
+    #[error("x509: malformed SubjectPublicKeyInfo")]
+    MalformedPublicKeyInfo,
+
+    #[error("x509: malformed EC private key")]
+    MalformedECPrivateKey,
+
+    #[error("x509: malformed PKCS#8 private key")]
+    MalformedPrivateKeyInfo,
+
+    #[error("x509: malformed PKCS#8 encrypted private key")]
+    MalformedEncryptedPrivateKeyInfo,
+
+    #[error("x509: unsupported encryption algorithm: {}", .0)]
+    UnsupportedEncryption(String),
+
+    #[error("x509: invalid PBKDF2 parameters")]
+    InvalidPBKDF2Parameters,
+
+    #[error("x509: decryption failed, the password may be incorrect")]
+    DecryptionFailed,
+
+    #[error("x509: malformed {}", .0)]
+    MalformedCertificate(&'static str),
+
+    #[error("x509: unsupported signature algorithm: {}", .0)]
+    UnsupportedSignatureAlgorithm(String),
+
+    #[error("x509: inner and outer signature algorithm identifiers don't match")]
+    SignatureAlgorithmMismatch,
+
+    #[error("x509: certificate contains duplicate extension {}", .0)]
+    DuplicateExtension(String),
+
+    #[error("x509: invalid signature")]
+    InvalidSignature,
+
+    #[error("x509: trailing data after ASN.1 of {}", .0)]
+    TrailingData(&'static str),
+
+    #[error("x509: unsupported public key algorithm: {}", .0)]
+    UnsupportedAlgorithm(String),
+
+    #[error("x509: unsupported elliptic curve: {}", .0)]
+    UnsupportedCurve(String),
+
+    #[error("x509: unknown elliptic curve")]
+    UnknownCurve,
+
+    #[error("x509: unsupported {} version {}", .0, .1)]
+    UnsupportedVersion(&'static str, i64),
+
+    #[error("x509: public key does not match the private key")]
+    PublicKeyMismatch,
+
+    #[error(transparent)]
+    SM2(#[from] SM2Error),
+
+    #[error(transparent)]
+    ASN1(#[from] cryptobyte::Error),
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,19 @@
+// Module x509 parses and serializes the SM2 keys in the standard containers
+// and parses the SM2 certificates,
+// the layout follows the Go's crypto/x509:
+// - pkix: SubjectPublicKeyInfo
+// - sec1: ECPrivateKey, RFC 5915
+// - pkcs8: PrivateKeyInfo, RFC 5208
+// - certificate: Certificate, RFC 5280
+
+pub mod certificate;
+mod errors;
+pub mod pkcs8;
+pub mod pkix;
+pub mod sec1;
+
+pub use certificate::*;
+pub use errors::{Error, Result};
+pub use pkcs8::*;
+pub use pkix::*;
+pub use sec1::*;
@@ -0,0 +1,324 @@
+use alloc::vec::Vec;
+use rand::Rng;
+
+use crate::blockmode::cbc::CBCMode;
+use crate::blockmode::padding::Padding;
+use crate::cryptobyte::oid::{OidHMACWithSM3, OidNamedCurveSM2, OidPBES2, OidPBKDF2, OidSM4CBC};
+use crate::cryptobyte::{Builder, Parser, INTEGER, NULL};
+use crate::kdf::pbkdf2_hmac_sm3;
+use crate::sm2::PrivateKey;
+use crate::sm4;
+
+use super::pkix::{add_sm2_algorithm, read_sm2_algorithm};
+use super::sec1::{marshal_ec_private_key_with_oid, parse_ec_private_key_with_oid};
+use super::{Error, Result};
+
+// PrivateKeyInfo ::= SEQUENCE {
+//     version                   Version,
+//     privateKeyAlgorithm       AlgorithmIdentifier,
+//     privateKey                OCTET STRING,
+//     attributes           [0]  IMPLICIT Attributes OPTIONAL }
+// The privateKey is an ECPrivateKey without the parameters.
+pub fn marshal_pkcs8_private_key(sk: &PrivateKey) -> Result<Vec<u8>> {
+    let key = marshal_ec_private_key_with_oid(sk, None)?;
+    let mut b = Builder::new(Vec::with_capacity(key.len() + 32));
+    b.add_asn1_sequence(|b| {
+        b.add_asn1_i64(0);
+        add_sm2_algorithm(b);
+        b.add_asn1_octet_string(&key);
+    });
+    Ok(b.take()?)
+}
+
+// Both version 0 (RFC 5208) and version 1 (RFC 5958) are accepted, the
+// attributes and the public key of the latter are ignored.
+pub fn parse_pkcs8_private_key(der: &[u8]) -> Result<PrivateKey> {
+    let mut input = Parser::new(der);
+    let mut info = input.read_asn1_sequence().ok_or(Error::MalformedPrivateKeyInfo)?;
+    if !input.empty() {
+        return Err(Error::TrailingData("PrivateKeyInfo"));
+    }
+    let version = info.read_asn1_i64().ok_or(Error::MalformedPrivateKeyInfo)?;
+    if version != 0 && version != 1 {
+        return Err(Error::UnsupportedVersion("PrivateKeyInfo", version));
+    }
+    read_sm2_algorithm(&mut info)?;
+    let key = info.read_asn1_octet_string().ok_or(Error::MalformedPrivateKeyInfo)?;
+    parse_ec_private_key_with_oid(Some(&OidNamedCurveSM2), key)
+}
+
+pub const PBKDF2_SALT_SIZE: usize = 16;
+
+// The iteration count is read from the untrusted input, a larger one is
+// rejected instead of spending minutes in PBKDF2. openssl uses 2048 by default.
+pub const PBKDF2_MAX_ITERATIONS: u32 = 10_000_000;
+
+// The PRF of PBKDF2 defaults to hmacWithSHA1 when absent, which is not supported.
+const OID_HMAC_WITH_SHA1: &str = "1.2.840.113549.2.7";
+
+// EncryptedPrivateKeyInfo ::= SEQUENCE {
+//     encryptionAlgorithm  AlgorithmIdentifier {{ PBES2 }},
+//     encryptedData        OCTET STRING }
+// PBES2-params ::= SEQUENCE {
+//     keyDerivationFunc AlgorithmIdentifier {{ PBKDF2 }},
+//     encryptionScheme  AlgorithmIdentifier {{ sm4-cbc, IV }} }
+// PBKDF2-params ::= SEQUENCE {
+//     salt            OCTET STRING,
+//     iterationCount  INTEGER,
+//     keyLength       INTEGER OPTIONAL,
+//     prf             AlgorithmIdentifier {{ hmacWithSM3 }} }
+// The salt and IV are generated by rng.
+pub fn marshal_encrypted_pkcs8_private_key(sk: &PrivateKey, password: &[u8], iterations: u32, rng: &mut impl Rng) -> Result<Vec<u8>> {
+    let mut salt = [0u8; PBKDF2_SALT_SIZE];
+    rng.fill(&mut salt);
+    let mut iv = [0u8; sm4::BLOCK_SIZE];
+    rng.fill(&mut iv);
+    marshal_encrypted_pkcs8_private_key_with(sk, password, &salt, iterations, &iv)
+}
+
+pub fn marshal_encrypted_pkcs8_private_key_with(
+    sk: &PrivateKey,
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    iv: &[u8; sm4::BLOCK_SIZE],
+) -> Result<Vec<u8>> {
+    if iterations == 0 || iterations > PBKDF2_MAX_ITERATIONS {
+        return Err(Error::InvalidPBKDF2Parameters);
+    }
+    let mut data = marshal_pkcs8_private_key(sk)?;
+    Padding::PKCS7.pad(&mut data, sm4::BLOCK_SIZE);
+
+    let mut key = [0u8; sm4::KEY_SIZE];
+    pbkdf2_hmac_sm3(password, salt, iterations, &mut key).map_err(|_| Error::InvalidPBKDF2Parameters)?;
+    // data is padded to blocks, the encryption never fails.
+    CBCMode::new(sm4::Cipher::new(&key)).encrypt_inplace(iv, &mut data).unwrap();
+    key.fill(0);
+
+    let mut b = Builder::new(Vec::with_capacity(data.len() + 128));
+    b.add_asn1_sequence(|b| {
+        b.add_asn1_sequence(|b| {
+            b.add_asn1_object_identifier(&OidPBES2);
+            b.add_asn1_sequence(|b| {
+                b.add_asn1_sequence(|b| {
+                    b.add_asn1_object_identifier(&OidPBKDF2);
+                    b.add_asn1_sequence(|b| {
+                        b.add_asn1_octet_string(salt);
+                        b.add_asn1_u64(iterations as u64);
+                        b.add_asn1_sequence(|b| {
+                            b.add_asn1_object_identifier(&OidHMACWithSM3);
+                            b.add_asn1_null();
+                        });
+                    });
+                });
+                b.add_asn1_sequence(|b| {
+                    b.add_asn1_object_identifier(&OidSM4CBC);
+                    b.add_asn1_octet_string(iv);
+                });
+            });
+        });
+        b.add_asn1_octet_string(&data);
+    });
+    Ok(b.take()?)
+}
+
+// The salt and the iteration count are taken from der.
+pub fn parse_encrypted_pkcs8_private_key(der: &[u8], password: &[u8]) -> Result<PrivateKey> {
+    let mut input = Parser::new(der);
+    let mut info = input.read_asn1_sequence().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    if !input.empty() {
+        return Err(Error::TrailingData("EncryptedPrivateKeyInfo"));
+    }
+    let mut alg = info.read_asn1_sequence().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    let oid = alg.read_asn1_object_identifier().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    if oid != OidPBES2 {
+        return Err(Error::UnsupportedEncryption(oid.to_string()));
+    }
+    let mut params = alg.read_asn1_sequence().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    let (salt, iterations) = read_pbkdf2_params(&mut params)?;
+    let iv = read_sm4_cbc_params(&mut params)?;
+    let data = info.read_asn1_octet_string().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    if !params.empty() || !alg.empty() || !info.empty() {
+        return Err(Error::MalformedEncryptedPrivateKeyInfo);
+    }
+    if data.is_empty() || data.len() % sm4::BLOCK_SIZE != 0 {
+        return Err(Error::MalformedEncryptedPrivateKeyInfo);
+    }
+
+    let mut key = [0u8; sm4::KEY_SIZE];
+    pbkdf2_hmac_sm3(password, salt, iterations, &mut key).map_err(|_| Error::InvalidPBKDF2Parameters)?;
+    let mut plain = data.to_vec();
+    // the length is checked, the decryption never fails.
+    CBCMode::new(sm4::Cipher::new(&key)).decrypt_inplace(iv, &mut plain).unwrap();
+    key.fill(0);
+
+    let result = match Padding::PKCS7.unpad(&plain, sm4::BLOCK_SIZE) {
+        // A wrong password almost always breaks the padding, otherwise the DER.
+        Ok(n) => parse_pkcs8_private_key(&plain[..n]).map_err(|e| match e {
+            Error::MalformedPrivateKeyInfo
+            | Error::MalformedECPrivateKey
+            | Error::MalformedAlgorithmIdentifier
+            | Error::TrailingData(_) => Error::DecryptionFailed,
+            e => e,
+        }),
+        Err(_) => Err(Error::DecryptionFailed),
+    };
+    plain.fill(0);
+    result
+}
+
+fn read_pbkdf2_params<'a>(params: &mut Parser<'a>) -> Result<(&'a [u8], u32)> {
+    let mut kdf = params.read_asn1_sequence().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    let oid = kdf.read_asn1_object_identifier().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    if oid != OidPBKDF2 {
+        return Err(Error::UnsupportedEncryption(oid.to_string()));
+    }
+    let mut p = kdf.read_asn1_sequence().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    let salt = p.read_asn1_octet_string().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    let iterations = p.read_asn1_i64().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    if iterations < 1 || iterations > PBKDF2_MAX_ITERATIONS as i64 {
+        return Err(Error::InvalidPBKDF2Parameters);
+    }
+    if p.peek_tag() == Some(INTEGER) {
+        let key_length = p.read_asn1_i64().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+        if key_length != sm4::KEY_SIZE as i64 {
+            return Err(Error::InvalidPBKDF2Parameters);
+        }
+    }
+    if p.empty() {
+        return Err(Error::UnsupportedEncryption(OID_HMAC_WITH_SHA1.into()));
+    }
+    let mut prf = p.read_asn1_sequence().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    let oid = prf.read_asn1_object_identifier().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    if oid != OidHMACWithSM3 {
+        return Err(Error::UnsupportedEncryption(oid.to_string()));
+    }
+    // the parameters of the PRF are NULL or absent.
+    if !prf.empty() && prf.read_asn1(NULL) != Some(&[]) {
+        return Err(Error::MalformedEncryptedPrivateKeyInfo);
+    }
+    if !prf.empty() || !p.empty() || !kdf.empty() {
+        return Err(Error::MalformedEncryptedPrivateKeyInfo);
+    }
+    Ok((salt, iterations as u32))
+}
+
+fn read_sm4_cbc_params<'a>(params: &mut Parser<'a>) -> Result<&'a [u8]> {
+    let mut scheme = params.read_asn1_sequence().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    let oid = scheme.read_asn1_object_identifier().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    if oid != OidSM4CBC {
+        return Err(Error::UnsupportedEncryption(oid.to_string()));
+    }
+    let iv = scheme.read_asn1_octet_string().ok_or(Error::MalformedEncryptedPrivateKeyInfo)?;
+    if iv.len() != sm4::BLOCK_SIZE || !scheme.empty() {
+        return Err(Error::MalformedEncryptedPrivateKeyInfo);
+    }
+    Ok(iv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    // openssl pkcs8 -topk8 -nocrypt -outform DER
+    const PKCS8: [u8; 138] = hex!("308187020100301306072a8648ce3d020106082a811ccf5501822d046d306b02010104202ac464aef2460dbd9c2230812ebaf66a5def844a1743d0ebed7dba8594519e11a14403420004a36703f50976815394c468dc8084878608f111016064e84dbb4cf9dc66590857af1af61eeabd2305e7671b4e8303763483b029df854b7aad3e3f09bd3847eaaf");
+
+    #[test]
+    fn test_pkcs8_private_key() {
+        let sk = parse_pkcs8_private_key(&PKCS8).unwrap();
+        assert_eq!(sk.to_be_bytes(), hex!("2ac464aef2460dbd9c2230812ebaf66a5def844a1743d0ebed7dba8594519e11"));
+        assert_eq!(marshal_pkcs8_private_key(&sk).unwrap(), PKCS8);
+
+        let sk = PrivateKey::new(&mut rand::rng());
+        let der = marshal_pkcs8_private_key(&sk).unwrap();
+        let sk2 = parse_pkcs8_private_key(&der).unwrap();
+        assert_eq!(sk2.to_be_bytes(), sk.to_be_bytes());
+        assert_eq!(sk2.public(), sk.public());
+    }
+
+    #[test]
+    fn test_pkcs8_private_key_errors() {
+        let mut der = PKCS8;
+        der[5] = 2;
+        assert!(matches!(parse_pkcs8_private_key(&der), Err(Error::UnsupportedVersion(_, 2))));
+
+        // the algorithm is 1.2.840.113549.1 (pkcs) instead of id-ecPublicKey, both 9 bytes.
+        let mut der = PKCS8;
+        der[8..17].copy_from_slice(&hex!("06072a864886f70d01"));
+        assert!(matches!(parse_pkcs8_private_key(&der), Err(Error::UnsupportedAlgorithm(_))));
+
+        assert!(matches!(parse_pkcs8_private_key(&PKCS8[..137]), Err(Error::MalformedPrivateKeyInfo)));
+    }
+
+    // openssl pkcs8 -topk8 -v2 sm4-cbc -v2prf hmacWithSM3 -iter 2048 -passout pass:opengm123 -outform DER
+    const ENCRYPTED_PKCS8: [u8; 248] = hex!("3081f5306006092a864886f70d01050d3053303306092a864886f70d01050c302604107b4d5fa0631a1e7f39f963456806774b02020800300e060a2a811ccf5501831103010500301c06082a811ccf550168020410c9484971e56feee8cfcb70d194c6b48104819024d04a6c997d7a9a2594463874183ab72e33127eaf428e901e0017bb6b70c40268a1d02f2f5e28018d5520a593875de19e9ea519c3b2d2560ec43b1213530de228892914ce7a8bde9ef276704a6e1a218a10f59ae5f171c40b3aba4580bacb65d539272054342ca6eef930b4c6bceae60049797bba5fff0ab466379d50f921d1b438a02ae9809d8f37a94f9bdb4e30f3");
+
+    #[test]
+    fn test_encrypted_pkcs8_private_key() {
+        let sk = parse_encrypted_pkcs8_private_key(&ENCRYPTED_PKCS8, b"opengm123").unwrap();
+        assert_eq!(sk.to_be_bytes(), hex!("2ac464aef2460dbd9c2230812ebaf66a5def844a1743d0ebed7dba8594519e11"));
+
+        let der = marshal_encrypted_pkcs8_private_key_with(
+            &sk,
+            b"opengm123",
+            &hex!("7b4d5fa0631a1e7f39f963456806774b"),
+            2048,
+            &hex!("c9484971e56feee8cfcb70d194c6b481"),
+        )
+        .unwrap();
+        assert_eq!(der, ENCRYPTED_PKCS8);
+
+        let mut rng = rand::rng();
+        let sk = PrivateKey::new(&mut rng);
+        let der = marshal_encrypted_pkcs8_private_key(&sk, b"secret", 100, &mut rng).unwrap();
+        let sk2 = parse_encrypted_pkcs8_private_key(&der, b"secret").unwrap();
+        assert_eq!(sk2.to_be_bytes(), sk.to_be_bytes());
+    }
+
+    #[test]
+    fn test_encrypted_pkcs8_private_key_errors() {
+        assert!(matches!(
+            parse_encrypted_pkcs8_private_key(&ENCRYPTED_PKCS8, b"opengm124"),
+            Err(Error::DecryptionFailed)
+        ));
+
+        // iterations = 0
+        let mut der = ENCRYPTED_PKCS8;
+        der[53..55].fill(0);
+        assert!(matches!(
+            parse_encrypted_pkcs8_private_key(&der, b"opengm123"),
+            Err(Error::InvalidPBKDF2Parameters)
+        ));
+
+        // iterations = PBKDF2_MAX_ITERATIONS + 1, 2 bytes longer.
+        let mut der = ENCRYPTED_PKCS8.to_vec();
+        der.splice(52..55, hex!("04 00989681"));
+        for i in [2, 4, 17, 19, 32] {
+            der[i] += 2;
+        }
+        assert!(matches!(
+            parse_encrypted_pkcs8_private_key(&der, b"opengm123"),
+            Err(Error::InvalidPBKDF2Parameters)
+        ));
+        let mut rng = rand::rng();
+        let sk = PrivateKey::new(&mut rng);
+        assert!(matches!(
+            marshal_encrypted_pkcs8_private_key(&sk, b"secret", PBKDF2_MAX_ITERATIONS + 1, &mut rng),
+            Err(Error::InvalidPBKDF2Parameters)
+        ));
+
+        // sm4-ecb instead of sm4-cbc.
+        let mut der = ENCRYPTED_PKCS8;
+        der[82] = 1;
+        assert!(matches!(
+            parse_encrypted_pkcs8_private_key(&der, b"opengm123"),
+            Err(Error::UnsupportedEncryption(_))
+        ));
+
+        assert!(matches!(
+            parse_encrypted_pkcs8_private_key(&ENCRYPTED_PKCS8[..247], b"opengm123"),
+            Err(Error::MalformedEncryptedPrivateKeyInfo)
+        ));
+    }
+}
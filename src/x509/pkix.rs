@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+
+use crate::cryptobyte::asn1::BitString;
+use crate::cryptobyte::oid::{OidNamedCurveSM2, OidPublicKeyECDSA};
+use crate::cryptobyte::{Builder, Parser};
+use crate::sm2::{PointFormat, PublicKey};
+
+use super::{Error, Result};
+
+// AlgorithmIdentifier ::= SEQUENCE { id-ecPublicKey, sm2p256v1 }
+pub(crate) fn add_sm2_algorithm(b: &mut Builder) {
+    b.add_asn1_sequence(|b| {
+        b.add_asn1_object_identifier(&OidPublicKeyECDSA);
+        b.add_asn1_object_identifier(&OidNamedCurveSM2);
+    });
+}
+
+// Reads an AlgorithmIdentifier, only the SM2 named curve is supported.
+pub(crate) fn read_sm2_algorithm(input: &mut Parser) -> Result<()> {
+    let mut alg = input.read_asn1_sequence().ok_or(Error::MalformedAlgorithmIdentifier)?;
+    let oid = alg.read_asn1_object_identifier().ok_or(Error::MalformedAlgorithmIdentifier)?;
+    if oid != OidPublicKeyECDSA {
+        return Err(Error::UnsupportedAlgorithm(oid.to_string()));
+    }
+    let curve = alg.read_asn1_object_identifier().ok_or(Error::MalformedAlgorithmIdentifier)?;
+    if curve != OidNamedCurveSM2 {
+        return Err(Error::UnsupportedCurve(curve.to_string()));
+    }
+    if !alg.empty() {
+        return Err(Error::MalformedAlgorithmIdentifier);
+    }
+    Ok(())
+}
+
+// SubjectPublicKeyInfo ::= SEQUENCE {
+//     algorithm         AlgorithmIdentifier,
+//     subjectPublicKey  BIT STRING }
+pub fn marshal_pkix_public_key(pk: &PublicKey) -> Result<Vec<u8>> {
+    let point = pk.to_sec1_bytes(PointFormat::Uncompressed);
+    let mut b = Builder::new(Vec::with_capacity(91));
+    b.add_asn1_sequence(|b| {
+        add_sm2_algorithm(b);
+        b.add_asn1_bit_string(&BitString::new(point.as_slice(), point.len() * 8));
+    });
+    Ok(b.take()?)
+}
+
+// The public key may be in any of the SEC1 forms, and is always validated.
+pub fn parse_pkix_public_key(der: &[u8]) -> Result<PublicKey> {
+    let mut input = Parser::new(der);
+    let mut spki = input.read_asn1_sequence().ok_or(Error::MalformedPublicKeyInfo)?;
+    if !input.empty() {
+        return Err(Error::TrailingData("SubjectPublicKeyInfo"));
+    }
+    read_sm2_algorithm(&mut spki)?;
+    let key = spki.read_asn1_bit_string().ok_or(Error::MalformedPublicKeyInfo)?;
+    if key.bit_length % 8 != 0 || !spki.empty() {
+        return Err(Error::MalformedPublicKeyInfo);
+    }
+    Ok(PublicKey::from_sec1_bytes(key.as_slice())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sm2::error::SM2Error;
+    use hex_literal::hex;
+
+    // openssl pkey -pubout -outform DER
+    const SPKI: [u8; 91] = hex!("3059301306072a8648ce3d020106082a811ccf5501822d03420004a36703f50976815394c468dc8084878608f111016064e84dbb4cf9dc66590857af1af61eeabd2305e7671b4e8303763483b029df854b7aad3e3f09bd3847eaaf");
+
+    #[test]
+    fn test_pkix_public_key() {
+        let pk = parse_pkix_public_key(&SPKI).unwrap();
+        assert_eq!(pk.x.to_be_bytes(), SPKI[27..59]);
+        assert_eq!(marshal_pkix_public_key(&pk).unwrap(), SPKI);
+    }
+
+    #[test]
+    fn test_pkix_public_key_errors() {
+        assert!(matches!(parse_pkix_public_key(&SPKI[..90]), Err(Error::MalformedPublicKeyInfo)));
+
+        let mut der = SPKI.to_vec();
+        der.push(0);
+        assert!(matches!(parse_pkix_public_key(&der), Err(Error::TrailingData(_))));
+
+        // prime256v1 instead of sm2p256v1.
+        let mut der = SPKI;
+        der[13..23].copy_from_slice(&hex!("06082a8648ce3d030107"));
+        assert!(matches!(parse_pkix_public_key(&der), Err(Error::UnsupportedCurve(_))));
+
+        let mut der = SPKI;
+        der[90] ^= 1;
+        assert!(matches!(parse_pkix_public_key(&der), Err(Error::SM2(SM2Error::InvalidPoint))));
+    }
+}
@@ -0,0 +1,157 @@
+use alloc::vec::Vec;
+
+use crate::cryptobyte::asn1::{BitString, ObjectIdentifier};
+use crate::cryptobyte::oid::OidNamedCurveSM2;
+use crate::cryptobyte::{Builder, Parser, Tag};
+use crate::sm2::{PointFormat, PrivateKey, PublicKey};
+
+use super::{Error, Result};
+
+const EC_PRIVATE_KEY_VERSION: i64 = 1;
+const PRIVATE_KEY_SIZE: usize = 32;
+
+const TAG_PARAMETERS: Tag = Tag(0).constructed().context_specific();
+const TAG_PUBLIC_KEY: Tag = Tag(1).constructed().context_specific();
+
+// ECPrivateKey ::= SEQUENCE {
+//     version        INTEGER { ecPrivkeyVer1(1) },
+//     privateKey     OCTET STRING,
+//     parameters [0] ECParameters {{ NamedCurve }} OPTIONAL,
+//     publicKey  [1] BIT STRING OPTIONAL }
+pub fn marshal_ec_private_key(sk: &PrivateKey) -> Result<Vec<u8>> {
+    marshal_ec_private_key_with_oid(sk, Some(&OidNamedCurveSM2))
+}
+
+pub fn parse_ec_private_key(der: &[u8]) -> Result<PrivateKey> {
+    parse_ec_private_key_with_oid(None, der)
+}
+
+// The parameters are omitted in PKCS#8, where the curve is in the algorithm identifier.
+pub(crate) fn marshal_ec_private_key_with_oid(sk: &PrivateKey, oid: Option<&ObjectIdentifier>) -> Result<Vec<u8>> {
+    let d = sk.to_be_bytes();
+    let point = sk.public().to_sec1_bytes(PointFormat::Uncompressed);
+    let mut b = Builder::new(Vec::with_capacity(121));
+    b.add_asn1_sequence(|b| {
+        b.add_asn1_i64(EC_PRIVATE_KEY_VERSION);
+        b.add_asn1_octet_string(&d);
+        if let Some(oid) = oid {
+            b.add_asn1(TAG_PARAMETERS, |b| b.add_asn1_object_identifier(oid));
+        }
+        b.add_asn1(TAG_PUBLIC_KEY, |b| {
+            b.add_asn1_bit_string(&BitString::new(point.as_slice(), point.len() * 8));
+        });
+    });
+    Ok(b.take()?)
+}
+
+// named_curve is the curve from the PKCS#8 algorithm identifier if any,
+// it takes precedence over the parameters in the key as in Go.
+pub(crate) fn parse_ec_private_key_with_oid(named_curve: Option<&ObjectIdentifier>, der: &[u8]) -> Result<PrivateKey> {
+    let mut input = Parser::new(der);
+    let mut key = input.read_asn1_sequence().ok_or(Error::MalformedECPrivateKey)?;
+    if !input.empty() {
+        return Err(Error::TrailingData("ECPrivateKey"));
+    }
+
+    let version = key.read_asn1_i64().ok_or(Error::MalformedECPrivateKey)?;
+    if version != EC_PRIVATE_KEY_VERSION {
+        return Err(Error::UnsupportedVersion("ECPrivateKey", version));
+    }
+    let mut d = key.read_asn1_octet_string().ok_or(Error::MalformedECPrivateKey)?;
+    let parameters = key.read_optional_asn1(TAG_PARAMETERS).ok_or(Error::MalformedECPrivateKey)?;
+    let public_key = key.read_optional_asn1(TAG_PUBLIC_KEY).ok_or(Error::MalformedECPrivateKey)?;
+    if !key.empty() {
+        return Err(Error::MalformedECPrivateKey);
+    }
+
+    let curve = match (named_curve, parameters) {
+        (Some(oid), _) => *oid,
+        (None, Some(parameters)) => {
+            let mut parameters = Parser::new(parameters);
+            let oid = parameters.read_asn1_object_identifier().ok_or(Error::MalformedECPrivateKey)?;
+            if !parameters.empty() {
+                return Err(Error::MalformedECPrivateKey);
+            }
+            oid
+        }
+        (None, None) => return Err(Error::UnknownCurve),
+    };
+    if curve != OidNamedCurveSM2 {
+        return Err(Error::UnsupportedCurve(curve.to_string()));
+    }
+
+    // Some implementations drop the leading zeros of d, and some
+    // add extra ones, accept both as Go does.
+    while d.len() > PRIVATE_KEY_SIZE && d[0] == 0 {
+        d = &d[1..];
+    }
+    if d.len() > PRIVATE_KEY_SIZE {
+        return Err(Error::MalformedECPrivateKey);
+    }
+    let mut padded = [0u8; PRIVATE_KEY_SIZE];
+    padded[PRIVATE_KEY_SIZE - d.len()..].copy_from_slice(d);
+    let sk = PrivateKey::from_be_slice(&padded)?;
+    padded.fill(0);
+
+    if let Some(public_key) = public_key {
+        let mut public_key = Parser::new(public_key);
+        let point = public_key.read_asn1_bit_string().ok_or(Error::MalformedECPrivateKey)?;
+        if point.bit_length % 8 != 0 || !public_key.empty() {
+            return Err(Error::MalformedECPrivateKey);
+        }
+        if PublicKey::from_sec1_bytes(point.as_slice())? != sk.public() {
+            return Err(Error::PublicKeyMismatch);
+        }
+    }
+    Ok(sk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    // openssl ec -outform DER
+    const EC_PRIVATE_KEY: [u8; 121] = hex!("307702010104202ac464aef2460dbd9c2230812ebaf66a5def844a1743d0ebed7dba8594519e11a00a06082a811ccf5501822da14403420004a36703f50976815394c468dc8084878608f111016064e84dbb4cf9dc66590857af1af61eeabd2305e7671b4e8303763483b029df854b7aad3e3f09bd3847eaaf");
+
+    #[test]
+    fn test_ec_private_key() {
+        let sk = parse_ec_private_key(&EC_PRIVATE_KEY).unwrap();
+        assert_eq!(sk.to_be_bytes(), EC_PRIVATE_KEY[7..39]);
+        assert_eq!(marshal_ec_private_key(&sk).unwrap(), EC_PRIVATE_KEY);
+
+        let sk = PrivateKey::new(&mut rand::rng());
+        let der = marshal_ec_private_key(&sk).unwrap();
+        assert_eq!(parse_ec_private_key(&der).unwrap().to_be_bytes(), sk.to_be_bytes());
+    }
+
+    #[test]
+    fn test_ec_private_key_short_scalar() {
+        // d = 1 with only one byte, no parameters and public key in the key.
+        let der = hex!("3006020101040101");
+        assert!(matches!(parse_ec_private_key(&der), Err(Error::UnknownCurve)));
+        let sk = parse_ec_private_key_with_oid(Some(&OidNamedCurveSM2), &der).unwrap();
+        let mut d = [0u8; 32];
+        d[31] = 1;
+        assert_eq!(sk.to_be_bytes(), d);
+    }
+
+    #[test]
+    fn test_ec_private_key_errors() {
+        let mut der = EC_PRIVATE_KEY;
+        der[4] = 2;
+        assert!(matches!(parse_ec_private_key(&der), Err(Error::UnsupportedVersion(_, 2))));
+
+        // the public key is not the one of d.
+        let mut der = EC_PRIVATE_KEY;
+        der[38] ^= 1;
+        assert!(matches!(parse_ec_private_key(&der), Err(Error::PublicKeyMismatch)));
+
+        // d = 0
+        let mut der = EC_PRIVATE_KEY;
+        der[7..39].fill(0);
+        assert!(matches!(parse_ec_private_key(&der), Err(Error::SM2(_))));
+
+        assert!(matches!(parse_ec_private_key(&EC_PRIVATE_KEY[..120]), Err(Error::MalformedECPrivateKey)));
+    }
+}